@@ -0,0 +1,173 @@
+use {
+    crate::{environ, requires_secure_execution},
+    core::{
+        error::Error,
+        fmt::{self, Display, Formatter},
+    },
+    std::{
+        ffi::{OsStr, OsString},
+        os::unix::ffi::OsStrExt,
+        vec::Vec,
+    },
+};
+
+/// An auditor for the environment block of the C library.
+///
+/// In contrast to [`std::env::vars_os`], the auditor sees every entry of the environment,
+/// including duplicates and entries that do not contain `=`. Such entries are used in
+/// attacks against set-user-ID programs because `getenv` and `unsetenv` might disagree
+/// about them.
+///
+/// ```
+/// use secure_execution::EnvAudit;
+///
+/// // SAFETY: No other threads exist.
+/// let findings = unsafe { EnvAudit::new().fatal_in_secure_execution(true).run() };
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct EnvAudit {
+    max_value_len: usize,
+    fatal: bool,
+}
+
+/// A suspicious entry found by [`EnvAudit`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Finding {
+    /// A variable that has already been set by a previous entry.
+    Duplicate {
+        /// The name of the variable.
+        name: OsString,
+    },
+    /// An entry that does not contain `=`.
+    MissingEquals {
+        /// The entry.
+        entry: OsString,
+    },
+    /// An entry that starts with `=`.
+    EmptyName {
+        /// The entry.
+        entry: OsString,
+    },
+    /// A variable whose name is not valid UTF-8.
+    NonUtf8Name {
+        /// The name of the variable.
+        name: OsString,
+    },
+    /// A variable whose value is longer than the configured limit.
+    OversizedValue {
+        /// The name of the variable.
+        name: OsString,
+        /// The length of the value in bytes.
+        len: usize,
+    },
+}
+
+/// The error returned by [`EnvAudit::run`] if findings are fatal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditError {
+    /// The findings. This is never empty.
+    pub findings: Vec<Finding>,
+}
+
+impl Default for EnvAudit {
+    fn default() -> Self {
+        Self {
+            max_value_len: 4096,
+            fatal: false,
+        }
+    }
+}
+
+impl EnvAudit {
+    /// Creates an auditor with a value limit of 4096 bytes whose findings are not fatal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum length of values in bytes.
+    pub fn max_value_len(mut self, len: usize) -> Self {
+        self.max_value_len = len;
+        self
+    }
+
+    /// Sets whether any finding is an error if secure execution is required.
+    pub fn fatal_in_secure_execution(mut self, fatal: bool) -> Self {
+        self.fatal = fatal;
+        self
+    }
+
+    /// Audits the environment.
+    ///
+    /// The findings are returned in the order of the entries in the environment. If
+    /// findings are fatal, [`requires_secure_execution`] returns `true`, and there are
+    /// any findings, this function returns an error instead.
+    ///
+    /// # Safety
+    ///
+    /// The environment must not be modified concurrently, for example by another thread
+    /// calling [`std::env::set_var`].
+    pub unsafe fn run(&self) -> Result<Vec<Finding>, AuditError> {
+        let mut names = Vec::<&[u8]>::new();
+        let mut findings = Vec::new();
+        // SAFETY: The caller guarantees that the environment is not modified.
+        for entry in unsafe { environ::entries() } {
+            let os = |b: &[u8]| OsString::from(OsStr::from_bytes(b));
+            let (name, value) = match entry.iter().position(|&b| b == b'=') {
+                None => {
+                    findings.push(Finding::MissingEquals { entry: os(entry) });
+                    continue;
+                }
+                Some(0) => {
+                    findings.push(Finding::EmptyName { entry: os(entry) });
+                    continue;
+                }
+                Some(pos) => (&entry[..pos], &entry[pos + 1..]),
+            };
+            if names.contains(&name) {
+                findings.push(Finding::Duplicate { name: os(name) });
+            } else {
+                names.push(name);
+            }
+            if core::str::from_utf8(name).is_err() {
+                findings.push(Finding::NonUtf8Name { name: os(name) });
+            }
+            if value.len() > self.max_value_len {
+                findings.push(Finding::OversizedValue {
+                    name: os(name),
+                    len: value.len(),
+                });
+            }
+        }
+        if self.fatal && !findings.is_empty() && requires_secure_execution() {
+            return Err(AuditError { findings });
+        }
+        Ok(findings)
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Finding::Duplicate { name } => write!(f, "variable {name:?} is set multiple times"),
+            Finding::MissingEquals { entry } => write!(f, "entry {entry:?} does not contain `=`"),
+            Finding::EmptyName { entry } => write!(f, "entry {entry:?} has an empty name"),
+            Finding::NonUtf8Name { name } => write!(f, "variable {name:?} is not valid UTF-8"),
+            Finding::OversizedValue { name, len } => {
+                write!(f, "the value of variable {name:?} is {len} bytes long")
+            }
+        }
+    }
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "the environment contains suspicious entries")?;
+        for finding in &self.findings {
+            write!(f, "; {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for AuditError {}
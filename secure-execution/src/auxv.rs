@@ -0,0 +1,444 @@
+//! Access to the auxiliary vector on Linux and Android.
+//!
+//! The auxiliary vector is a list of key-value pairs that the kernel passes to every
+//! process on its initial stack. Several of its entries describe the security context in
+//! which the executable was started. See [`getauxval(3)`] for details.
+//!
+//! [`getauxval(3)`]: https://man7.org/linux/man-pages/man3/getauxval.3.html
+//!
+//! Programs that define their own entry point can record the location of the auxiliary
+//! vector with [`init_from_initial_stack`] or [`init_from_environ`]. All functions in
+//! this crate then read the recorded vector instead of asking the C library or reading
+//! `/proc/self/auxv`. Note that this alone does not allow linking against C libraries
+//! that do not provide `getauxval`: The crate only stops referencing `getauxval` if the
+//! `raw-syscalls` feature is enabled and the target architecture is one of `x86_64`,
+//! `aarch64`, or `riscv64`.
+
+use {
+    crate::linux::{sys, EINTR, O_CLOEXEC, O_RDONLY},
+    core::{
+        ffi::{c_char, c_int, c_ulong, CStr},
+        fmt::{self, Debug, Formatter},
+        ptr,
+        sync::atomic::{
+            AtomicPtr, AtomicU8, AtomicUsize,
+            Ordering::{Acquire, Relaxed, Release},
+        },
+    },
+};
+
+/// End of the vector.
+pub const AT_NULL: c_ulong = 0;
+/// Address of the program headers of the executable.
+pub const AT_PHDR: c_ulong = 3;
+/// Size of a program header entry.
+pub const AT_PHENT: c_ulong = 4;
+/// Number of program headers.
+pub const AT_PHNUM: c_ulong = 5;
+/// System page size.
+pub const AT_PAGESZ: c_ulong = 6;
+/// Base address of the program interpreter.
+pub const AT_BASE: c_ulong = 7;
+/// Flags.
+pub const AT_FLAGS: c_ulong = 8;
+/// Entry point of the executable.
+pub const AT_ENTRY: c_ulong = 9;
+/// Real user ID of the process at the time of `execve`.
+pub const AT_UID: c_ulong = 11;
+/// Effective user ID of the process at the time of `execve`.
+pub const AT_EUID: c_ulong = 12;
+/// Real group ID of the process at the time of `execve`.
+pub const AT_GID: c_ulong = 13;
+/// Effective group ID of the process at the time of `execve`.
+pub const AT_EGID: c_ulong = 14;
+/// Address of a string identifying the hardware platform.
+pub const AT_PLATFORM: c_ulong = 15;
+/// Bit mask of hardware capabilities.
+pub const AT_HWCAP: c_ulong = 16;
+/// Frequency of `times(2)`.
+pub const AT_CLKTCK: c_ulong = 17;
+/// Whether the executable should be treated securely.
+pub const AT_SECURE: c_ulong = 23;
+/// Address of a string identifying the real platform.
+pub const AT_BASE_PLATFORM: c_ulong = 24;
+/// Address of 16 random bytes.
+pub const AT_RANDOM: c_ulong = 25;
+/// Further bit mask of hardware capabilities.
+pub const AT_HWCAP2: c_ulong = 26;
+/// Address of the pathname used to execute the program.
+pub const AT_EXECFN: c_ulong = 31;
+/// Address of the vDSO.
+pub const AT_SYSINFO_EHDR: c_ulong = 33;
+/// Minimal stack size for signal delivery.
+pub const AT_MINSIGSTKSZ: c_ulong = 51;
+
+/// Returns the value of an entry of the auxiliary vector.
+///
+/// This function returns `None` if the entry is not present. Unlike a plain call to
+/// `getauxval`, this allows distinguishing entries that are not present from entries
+/// whose value is `0`.
+///
+/// If the auxiliary vector has been recorded with [`init_from_initial_stack`] or
+/// [`init_from_environ`], the recorded vector is used. Otherwise, if the `raw-syscalls`
+/// feature is enabled, this function reads `/proc/self/auxv` instead of calling the C
+/// library and also returns `None` if that file cannot be read.
+pub fn getauxval(ty: c_ulong) -> Option<c_ulong> {
+    match recorded() {
+        Some(mut entries) => entries.find(|e| e.key == ty).map(|e| e.value),
+        _ => sys::getauxval(ty),
+    }
+}
+
+/// A snapshot of the security-relevant entries of the auxiliary vector.
+///
+/// Each accessor returns `None` if the corresponding entry is not present.
+#[derive(Copy, Clone, Debug)]
+pub struct Auxv {
+    secure: Option<c_ulong>,
+    uid: Option<c_ulong>,
+    euid: Option<c_ulong>,
+    gid: Option<c_ulong>,
+    egid: Option<c_ulong>,
+    execfn: Option<c_ulong>,
+    platform: Option<c_ulong>,
+    base_platform: Option<c_ulong>,
+    random: Option<c_ulong>,
+    hwcap: Option<c_ulong>,
+    hwcap2: Option<c_ulong>,
+    pagesz: Option<c_ulong>,
+    clktck: Option<c_ulong>,
+}
+
+impl Auxv {
+    /// Reads the entries of the auxiliary vector.
+    pub fn snapshot() -> Self {
+        match recorded() {
+            Some(entries) => {
+                Self::from_fn(|ty| entries.clone().find(|e| e.key == ty).map(|e| e.value))
+            }
+            _ => sys::auxv_snapshot(),
+        }
+    }
+
+    pub(crate) fn from_fn(getauxval: impl Fn(c_ulong) -> Option<c_ulong>) -> Self {
+        Self {
+            secure: getauxval(AT_SECURE),
+            uid: getauxval(AT_UID),
+            euid: getauxval(AT_EUID),
+            gid: getauxval(AT_GID),
+            egid: getauxval(AT_EGID),
+            execfn: getauxval(AT_EXECFN),
+            platform: getauxval(AT_PLATFORM),
+            base_platform: getauxval(AT_BASE_PLATFORM),
+            random: getauxval(AT_RANDOM),
+            hwcap: getauxval(AT_HWCAP),
+            hwcap2: getauxval(AT_HWCAP2),
+            pagesz: getauxval(AT_PAGESZ),
+            clktck: getauxval(AT_CLKTCK),
+        }
+    }
+
+    /// Returns the `AT_SECURE` entry.
+    pub fn secure(&self) -> Option<bool> {
+        self.secure.map(|v| v != 0)
+    }
+
+    /// Returns the `AT_UID` entry.
+    pub fn uid(&self) -> Option<u32> {
+        self.uid.map(|v| v as u32)
+    }
+
+    /// Returns the `AT_EUID` entry.
+    pub fn euid(&self) -> Option<u32> {
+        self.euid.map(|v| v as u32)
+    }
+
+    /// Returns the `AT_GID` entry.
+    pub fn gid(&self) -> Option<u32> {
+        self.gid.map(|v| v as u32)
+    }
+
+    /// Returns the `AT_EGID` entry.
+    pub fn egid(&self) -> Option<u32> {
+        self.egid.map(|v| v as u32)
+    }
+
+    /// Returns the string pointed to by the `AT_EXECFN` entry.
+    ///
+    /// This string is stored on the initial stack of the process. It is under the control
+    /// of whoever executed the program and must not be trusted.
+    pub fn execfn(&self) -> Option<&'static CStr> {
+        // SAFETY: The kernel places a nul-terminated string at this address that is never
+        // deallocated.
+        self.execfn.map(|v| unsafe { c_str(v) })
+    }
+
+    /// Returns the string pointed to by the `AT_PLATFORM` entry.
+    pub fn platform(&self) -> Option<&'static CStr> {
+        // SAFETY: The kernel places a nul-terminated string at this address that is never
+        // deallocated.
+        self.platform.map(|v| unsafe { c_str(v) })
+    }
+
+    /// Returns the string pointed to by the `AT_BASE_PLATFORM` entry.
+    pub fn base_platform(&self) -> Option<&'static CStr> {
+        // SAFETY: The kernel places a nul-terminated string at this address that is never
+        // deallocated.
+        self.base_platform.map(|v| unsafe { c_str(v) })
+    }
+
+    /// Returns whether the `AT_RANDOM` entry is present.
+    ///
+    /// The random bytes themselves are not exposed since they are usually used by the
+    /// C library for stack protectors and pointer mangling.
+    pub fn has_random(&self) -> bool {
+        self.random.is_some()
+    }
+
+    /// Returns the `AT_HWCAP` entry.
+    pub fn hwcap(&self) -> Option<c_ulong> {
+        self.hwcap
+    }
+
+    /// Returns the `AT_HWCAP2` entry.
+    pub fn hwcap2(&self) -> Option<c_ulong> {
+        self.hwcap2
+    }
+
+    /// Returns the `AT_PAGESZ` entry.
+    pub fn pagesz(&self) -> Option<c_ulong> {
+        self.pagesz
+    }
+
+    /// Returns the `AT_CLKTCK` entry.
+    pub fn clktck(&self) -> Option<c_ulong> {
+        self.clktck
+    }
+}
+
+unsafe fn c_str(v: c_ulong) -> &'static CStr {
+    unsafe { CStr::from_ptr(v as *const c_char) }
+}
+
+/// An entry of the auxiliary vector.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The type of the entry, for example [`AT_SECURE`].
+    pub key: c_ulong,
+    /// The value of the entry.
+    pub value: c_ulong,
+}
+
+const MAX_ENTRIES: usize = 64;
+
+/// An iterator over all entries of the auxiliary vector.
+///
+/// This iterator is created by [`entries`].
+#[derive(Clone)]
+pub struct Entries {
+    buf: [Entry; MAX_ENTRIES],
+    len: usize,
+    pos: usize,
+}
+
+impl Entries {
+    fn empty() -> Self {
+        Self {
+            buf: [Entry { key: 0, value: 0 }; MAX_ENTRIES],
+            len: 0,
+            pos: 0,
+        }
+    }
+}
+
+impl Debug for Entries {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(&self.buf[self.pos..self.len])
+            .finish()
+    }
+}
+
+impl Iterator for Entries {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.buf[self.pos..self.len].first().copied()?;
+        self.pos += 1;
+        Some(entry)
+    }
+}
+
+/// Returns an iterator over all entries of the auxiliary vector.
+///
+/// If the auxiliary vector has been recorded with [`init_from_initial_stack`] or
+/// [`init_from_environ`], the recorded vector is used. Otherwise, since the C library
+/// does not provide a way to enumerate the auxiliary vector, this function reads
+/// `/proc/self/auxv` and returns `None` if that file cannot be read, for example because
+/// `/proc` is not mounted. The result of the first successful read is cached since the
+/// file becomes unreadable when the process changes its ids.
+///
+/// The terminating [`AT_NULL`] entry is not part of the iterator.
+pub fn entries() -> Option<Entries> {
+    read_entries().ok()
+}
+
+/// Like [`entries`] but returns the error code if `/proc/self/auxv` cannot be read.
+pub(crate) fn read_entries() -> Result<Entries, c_int> {
+    if let Some(entries) = recorded() {
+        return Ok(entries);
+    }
+    if let Some(entries) = cached() {
+        return Ok(entries);
+    }
+    let mut entries = Entries::empty();
+    // SAFETY: Entry is repr(C) and consists of two c_ulong, so buf is plain old data
+    // without padding and has the layout of the file.
+    let buf = unsafe {
+        core::slice::from_raw_parts_mut(
+            entries.buf.as_mut_ptr().cast::<u8>(),
+            size_of_val(&entries.buf),
+        )
+    };
+    read_file(c"/proc/self/auxv", buf)?;
+    entries.len = entries
+        .buf
+        .iter()
+        .position(|e| e.key == AT_NULL)
+        .unwrap_or(MAX_ENTRIES);
+    cache(&entries);
+    Ok(entries)
+}
+
+// /proc/self/auxv becomes unreadable when the process changes its ids. Since the
+// auxiliary vector never changes, the first successful read is cached.
+static CACHE: [[AtomicUsize; 2]; MAX_ENTRIES] =
+    [const { [AtomicUsize::new(0), AtomicUsize::new(0)] }; MAX_ENTRIES];
+static CACHE_STATE: AtomicU8 = AtomicU8::new(CACHE_EMPTY);
+const CACHE_EMPTY: u8 = 0;
+const CACHE_WRITING: u8 = 1;
+const CACHE_READY: u8 = 2;
+
+fn cached() -> Option<Entries> {
+    if CACHE_STATE.load(Acquire) != CACHE_READY {
+        return None;
+    }
+    let mut entries = Entries::empty();
+    for (entry, [key, value]) in entries.buf.iter_mut().zip(&CACHE) {
+        entry.key = key.load(Relaxed) as c_ulong;
+        entry.value = value.load(Relaxed) as c_ulong;
+    }
+    entries.len = entries
+        .buf
+        .iter()
+        .position(|e| e.key == AT_NULL)
+        .unwrap_or(MAX_ENTRIES);
+    Some(entries)
+}
+
+fn cache(entries: &Entries) {
+    let res = CACHE_STATE.compare_exchange(CACHE_EMPTY, CACHE_WRITING, Relaxed, Relaxed);
+    if res.is_err() {
+        return;
+    }
+    for (entry, [key, value]) in entries.buf.iter().zip(&CACHE) {
+        key.store(entry.key as usize, Relaxed);
+        value.store(entry.value as usize, Relaxed);
+    }
+    CACHE_STATE.store(CACHE_READY, Release);
+}
+
+fn read_file(path: &CStr, buf: &mut [u8]) -> Result<usize, c_int> {
+    let fd = sys::open(path, O_RDONLY | O_CLOEXEC)?;
+    let mut pos = 0;
+    let res = loop {
+        if pos == buf.len() {
+            break Ok(pos);
+        }
+        match sys::read(fd, &mut buf[pos..]) {
+            Ok(0) => break Ok(pos),
+            Ok(n) => pos += n,
+            Err(EINTR) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    sys::close(fd);
+    res
+}
+
+static RECORDED: AtomicPtr<c_ulong> = AtomicPtr::new(ptr::null_mut());
+
+fn recorded() -> Option<Entries> {
+    let mut auxv = RECORDED.load(Acquire).cast_const();
+    if auxv.is_null() {
+        return None;
+    }
+    let mut entries = Entries::empty();
+    while entries.len < MAX_ENTRIES {
+        // SAFETY: The caller of init_from_environ guaranteed that the vector is valid and
+        // terminated by AT_NULL.
+        let [key, value] = unsafe { [*auxv, *auxv.add(1)] };
+        if key == AT_NULL {
+            break;
+        }
+        entries.buf[entries.len] = Entry { key, value };
+        entries.len += 1;
+        // SAFETY: The entry was not AT_NULL, so there is another entry.
+        auxv = unsafe { auxv.add(2) };
+    }
+    Some(entries)
+}
+
+/// Records the auxiliary vector from the initial stack of the process.
+///
+/// This function is intended for `#![no_main]` programs that define their own `_start`
+/// symbol. On entry to `_start`, the stack pointer points to `argc`, followed by the
+/// `argv` array, the environment, and the auxiliary vector.
+///
+/// This function must be called before the first call to
+/// [`requires_secure_execution`](crate::requires_secure_execution) since that function
+/// caches its result.
+///
+/// # Safety
+///
+/// `sp` must be the value of the stack pointer on entry to the program as set up by the
+/// kernel. The memory it points to must not be modified or deallocated for the rest of
+/// the lifetime of the process.
+pub unsafe fn init_from_initial_stack(sp: *const usize) {
+    // SAFETY: The caller guarantees that sp points to argc.
+    let argc = unsafe { *sp };
+    // SAFETY: The caller guarantees that the argv array, including its terminating null
+    // pointer, follows argc.
+    let envp = unsafe { sp.add(1 + argc + 1) };
+    // SAFETY: The caller guarantees that the environment follows the argv array.
+    unsafe { init_from_environ(envp.cast()) }
+}
+
+/// Records the auxiliary vector that follows the environment on the initial stack.
+///
+/// This function is intended for programs that receive the initial environment pointer,
+/// for example as the third argument of `main`. It walks past the terminating null
+/// pointer of the environment and records the auxiliary vector that follows it.
+///
+/// This function must be called before the first call to
+/// [`requires_secure_execution`](crate::requires_secure_execution) since that function
+/// caches its result.
+///
+/// # Safety
+///
+/// `envp` must be the environment pointer that the kernel placed on the initial stack.
+/// In particular, it must not be a copy of the environment, such as the `environ`
+/// variable after the environment has been modified. The memory it points to must not
+/// be modified or deallocated for the rest of the lifetime of the process.
+pub unsafe fn init_from_environ(envp: *const *const c_char) {
+    let mut p = envp;
+    // SAFETY: The caller guarantees that envp is terminated by a null pointer.
+    while !unsafe { *p }.is_null() {
+        // SAFETY: The current entry is not the terminating null pointer.
+        p = unsafe { p.add(1) };
+    }
+    // SAFETY: The caller guarantees that the auxiliary vector follows the environment.
+    let auxv = unsafe { p.add(1) };
+    RECORDED.store(auxv.cast::<c_ulong>().cast_mut(), Release);
+}
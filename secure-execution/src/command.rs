@@ -0,0 +1,121 @@
+use {
+    crate::{
+        is_unsafe_env_var, requires_secure_execution, SecureEnv, Validator, DEFAULT_TRUSTED_PATH,
+    },
+    std::{env, ffi::OsString, process::Command},
+};
+
+/// Extension methods for [`Command`] that apply secure-execution policies to child
+/// processes.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait SecureCommandExt: private::Sealed {
+    /// Replaces the environment of the child process according to the policy if secure
+    /// execution is required.
+    ///
+    /// If [`requires_secure_execution`] returns `true` or the policy applies in all
+    /// modes, this function
+    ///
+    /// - clears the environment of the child process, including variables set with
+    ///   [`Command::env`] before,
+    /// - adds each allowed variable of the current process whose value is accepted by
+    ///   its validator and that is not unsafe according to [`is_unsafe_env_var`],
+    /// - sets `PATH` to the trusted path of the policy.
+    ///
+    /// Otherwise, it does nothing.
+    ///
+    /// ```
+    /// use {
+    ///     secure_execution::{CommandEnv, SecureCommandExt, Validator},
+    ///     std::process::Command,
+    /// };
+    ///
+    /// let policy = CommandEnv::new().allow("TERM", Validator::no_slash_or_percent());
+    /// let mut cmd = Command::new("true");
+    /// cmd.secure_env(&policy).env("HELPER_MODE", "1");
+    /// ```
+    fn secure_env(&mut self, policy: &CommandEnv) -> &mut Self;
+}
+
+/// The environment that [`SecureCommandExt::secure_env`] passes to child processes.
+///
+/// The allowed variables can also be taken from a [`SecureEnv`] with
+/// [`CommandEnv::from`].
+#[derive(Debug)]
+pub struct CommandEnv {
+    allowed: SecureEnv,
+    path: OsString,
+    always: bool,
+}
+
+impl Default for CommandEnv {
+    fn default() -> Self {
+        SecureEnv::new().into()
+    }
+}
+
+impl From<SecureEnv> for CommandEnv {
+    /// Creates a policy that passes the variables allowed by `env`, uses
+    /// [`DEFAULT_TRUSTED_PATH`], and only applies if secure execution is required.
+    fn from(env: SecureEnv) -> Self {
+        Self {
+            allowed: env,
+            path: DEFAULT_TRUSTED_PATH.into(),
+            always: false,
+        }
+    }
+}
+
+impl CommandEnv {
+    /// Creates a policy that does not allow any variables, uses
+    /// [`DEFAULT_TRUSTED_PATH`], and only applies if secure execution is required.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Passes a variable to the child process if its value is accepted by the validator.
+    ///
+    /// See [`SecureEnv::allow`] for details. Allowing `PATH` has no effect.
+    pub fn allow(mut self, name: impl Into<OsString>, validator: Validator) -> Self {
+        self.allowed = self.allowed.allow(name, validator);
+        self
+    }
+
+    /// Sets the `PATH` of the child process.
+    pub fn trusted_path(mut self, path: impl Into<OsString>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets whether the policy also applies if secure execution is not required.
+    pub fn always(mut self, always: bool) -> Self {
+        self.always = always;
+        self
+    }
+}
+
+impl SecureCommandExt for Command {
+    fn secure_env(&mut self, policy: &CommandEnv) -> &mut Self {
+        if !policy.always && !requires_secure_execution() {
+            return self;
+        }
+        self.env_clear();
+        for (name, validator) in policy.allowed.rules() {
+            if name == "PATH" || is_unsafe_env_var(name.as_encoded_bytes()) {
+                continue;
+            }
+            if let Some(value) = env::var_os(name) {
+                if validator.accepts(&value) {
+                    self.env(name, value);
+                }
+            }
+        }
+        self.env("PATH", &policy.path)
+    }
+}
+
+pub(crate) mod private {
+    pub trait Sealed {}
+
+    impl Sealed for std::process::Command {}
+}
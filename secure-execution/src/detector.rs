@@ -0,0 +1,85 @@
+use core::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    mem, ptr,
+    sync::atomic::{
+        AtomicPtr,
+        Ordering::{AcqRel, Acquire},
+    },
+};
+
+/// A function that determines whether the running executable requires secure
+/// execution.
+///
+/// See the documentation of [`set_detector`] for details.
+pub type Detector = fn() -> bool;
+
+/// An error returned by [`set_detector`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SetDetectorError {
+    /// A detector has already been installed.
+    AlreadySet,
+    /// The property has already been determined without a custom detector.
+    AlreadyUsed,
+}
+
+impl Display for SetDetectorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            SetDetectorError::AlreadySet => "a detector has already been installed",
+            SetDetectorError::AlreadyUsed => {
+                "secure execution has already been determined without a custom detector"
+            }
+        };
+        f.write_str(msg)
+    }
+}
+
+impl Error for SetDetectorError {}
+
+static SEALED: u8 = 0;
+static DETECTOR: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+fn sealed() -> *mut () {
+    (&raw const SEALED).cast_mut().cast()
+}
+
+/// Installs a custom function that determines whether the running executable requires
+/// secure execution.
+///
+/// This is intended for platform integrators that know how to determine this property
+/// on platforms where this crate has to guess, for example, on embedded unix-like
+/// platforms. If a detector is installed, [`requires_secure_execution`] returns the
+/// value returned by the detector instead of using the built-in mechanism, and
+/// [`backend`] returns [`Backend::Custom`].
+///
+/// The detector can only be installed once and must be installed before any function
+/// of this crate has determined the property. Otherwise, this function returns an error
+/// and the detector is not used.
+///
+/// [`requires_secure_execution`]: crate::requires_secure_execution
+/// [`backend`]: crate::backend
+/// [`Backend::Custom`]: crate::Backend::Custom
+pub fn set_detector(detector: Detector) -> Result<(), SetDetectorError> {
+    let res = DETECTOR.compare_exchange(ptr::null_mut(), detector as *mut (), AcqRel, Acquire);
+    match res {
+        Ok(_) => Ok(()),
+        Err(p) if p == sealed() => Err(SetDetectorError::AlreadyUsed),
+        Err(_) => Err(SetDetectorError::AlreadySet),
+    }
+}
+
+/// Returns the installed detector and prevents detectors from being installed later.
+pub(crate) fn detector() -> Option<Detector> {
+    let p = match DETECTOR.compare_exchange(ptr::null_mut(), sealed(), AcqRel, Acquire) {
+        Ok(_) => return None,
+        Err(p) => p,
+    };
+    if p == sealed() {
+        return None;
+    }
+    // SAFETY: Other than the sentinel, only values of type Detector are stored in
+    // DETECTOR.
+    Some(unsafe { mem::transmute::<*mut (), Detector>(p) })
+}
@@ -0,0 +1,129 @@
+//! Secure-execution-aware home and XDG base directories.
+//!
+//! If [`requires_secure_execution`] returns `false`, the functions in this module follow
+//! the [XDG Base Directory Specification]: They return the corresponding environment
+//! variable if it contains an absolute path and a directory below the home directory
+//! otherwise.
+//!
+//! If [`requires_secure_execution`] returns `true`, all environment variables are
+//! ignored. The home directory is taken from the user database entry of the real user ID
+//! and must be a directory owned by that user. The other directories are derived from the
+//! home directory. If they exist, they must be directories, not symbolic links, and owned
+//! by the real user. Directories that do not exist are returned as well, and callers must
+//! create them without following symbolic links.
+//!
+//! [XDG Base Directory Specification]: https://specifications.freedesktop.org/basedir-spec/latest/
+
+use {
+    crate::{
+        passwd::{self, getuid},
+        requires_secure_execution,
+    },
+    std::{
+        env, format,
+        fs::{self, Metadata},
+        io::ErrorKind,
+        os::unix::fs::MetadataExt,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Returns the home directory of the user.
+///
+/// If secure execution is not required, this is `HOME` if it is set to a non-empty
+/// value.
+pub fn home_dir() -> Option<PathBuf> {
+    if !requires_secure_execution() {
+        if let Some(home) = env::var_os("HOME").filter(|h| !h.is_empty()) {
+            return Some(home.into());
+        }
+    }
+    let dir = PathBuf::from(passwd::getpwuid(getuid()).ok()??.dir);
+    if requires_secure_execution() && !fs::metadata(&dir).is_ok_and(|m| is_owned_dir(&m)) {
+        return None;
+    }
+    Some(dir)
+}
+
+/// Returns the directory for configuration files.
+///
+/// If secure execution is not required, this is `XDG_CONFIG_HOME` if it is set to an
+/// absolute path. Otherwise, it is `~/.config`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Returns the directory for cached files.
+///
+/// If secure execution is not required, this is `XDG_CACHE_HOME` if it is set to an
+/// absolute path. Otherwise, it is `~/.cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Returns the directory for data files.
+///
+/// If secure execution is not required, this is `XDG_DATA_HOME` if it is set to an
+/// absolute path. Otherwise, it is `~/.local/share`.
+pub fn data_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+/// Returns the directory for state files.
+///
+/// If secure execution is not required, this is `XDG_STATE_HOME` if it is set to an
+/// absolute path. Otherwise, it is `~/.local/state`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Returns the directory for runtime files such as sockets.
+///
+/// If secure execution is not required, this is `XDG_RUNTIME_DIR` if it is set to an
+/// absolute path. Otherwise, on Linux, it is `/run/user/$UID` if that directory exists,
+/// is owned by the real user, and is not accessible by other users.
+pub fn runtime_dir() -> Option<PathBuf> {
+    if !requires_secure_execution() {
+        if let Some(dir) = absolute_var("XDG_RUNTIME_DIR") {
+            return Some(dir);
+        }
+    }
+    if cfg!(not(target_os = "linux")) {
+        return None;
+    }
+    let dir = PathBuf::from(format!("/run/user/{}", getuid()));
+    let meta = fs::symlink_metadata(&dir).ok()?;
+    (is_owned_dir(&meta) && meta.mode() & 0o077 == 0).then_some(dir)
+}
+
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    if !requires_secure_execution() {
+        if let Some(dir) = absolute_var(var) {
+            return Some(dir);
+        }
+    }
+    let mut dir = home_dir()?;
+    let secure = requires_secure_execution();
+    let mut missing = false;
+    for component in Path::new(default).components() {
+        dir.push(component);
+        if secure && !missing {
+            match fs::symlink_metadata(&dir) {
+                Ok(meta) if is_owned_dir(&meta) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => missing = true,
+                _ => return None,
+            }
+        }
+    }
+    Some(dir)
+}
+
+fn absolute_var(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+}
+
+fn is_owned_dir(meta: &Metadata) -> bool {
+    meta.is_dir() && meta.uid() == getuid()
+}
@@ -0,0 +1,90 @@
+//! Functions that read the environment unless secure execution is required.
+
+use crate::requires_secure_execution;
+#[cfg(feature = "std")]
+use std::{
+    env::{self, VarError, VarsOs},
+    ffi::{OsStr, OsString},
+    string::String,
+};
+#[cfg(unix)]
+use {crate::sys, alloc::ffi::CString, core::ffi::CStr};
+
+/// Returns the value of an environment variable unless secure execution is required.
+///
+/// This function behaves like `secure_getenv` from glibc: If
+/// [`requires_secure_execution`] returns `true`, it returns `None`. Otherwise, it returns
+/// the value returned by `getenv`.
+///
+/// This function does not require `std` and is available if the `alloc` feature is
+/// enabled. If the `raw-syscalls` feature is used on Linux, the value is read from
+/// `/proc/self/environ`, which contains the initial environment of the process. On
+/// platforms where this crate does not know how to read the environment, this function
+/// always returns `None`.
+#[cfg(unix)]
+pub fn secure_getenv(name: &CStr) -> Option<CString> {
+    if requires_secure_execution() {
+        return None;
+    }
+    sys::getenv(name)
+}
+
+/// Returns the value of an environment variable unless secure execution is required.
+///
+/// If [`requires_secure_execution`] returns `true`, this function returns `None`.
+/// Otherwise, it behaves like [`std::env::var_os`].
+#[cfg(feature = "std")]
+pub fn secure_var_os<K: AsRef<OsStr>>(key: K) -> Option<OsString> {
+    if requires_secure_execution() {
+        return None;
+    }
+    env::var_os(key)
+}
+
+/// Returns the value of an environment variable unless secure execution is required.
+///
+/// If [`requires_secure_execution`] returns `true`, this function returns
+/// [`VarError::NotPresent`]. Otherwise, it behaves like [`std::env::var`].
+#[cfg(feature = "std")]
+pub fn secure_var<K: AsRef<OsStr>>(key: K) -> Result<String, VarError> {
+    if requires_secure_execution() {
+        return Err(VarError::NotPresent);
+    }
+    env::var(key)
+}
+
+/// Returns an iterator over the environment unless secure execution is required.
+///
+/// If [`requires_secure_execution`] returns `true`, the iterator is empty. Otherwise, it
+/// behaves like [`std::env::vars_os`].
+#[cfg(feature = "std")]
+pub fn secure_vars_os() -> SecureVarsOs {
+    SecureVarsOs {
+        inner: (!requires_secure_execution()).then(env::vars_os),
+    }
+}
+
+/// An iterator over the environment unless secure execution is required.
+///
+/// This iterator is created by [`secure_vars_os`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SecureVarsOs {
+    inner: Option<VarsOs>,
+}
+
+#[cfg(feature = "std")]
+impl Iterator for SecureVarsOs {
+    type Item = (OsString, OsString);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            Some(inner) => inner.size_hint(),
+            None => (0, Some(0)),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Access to the raw environment block of the C library.
+
+use core::ffi::c_char;
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "visionos",
+    ))] {
+        unsafe extern "C" {
+            // Executables on these platforms cannot access `environ` directly.
+            fn _NSGetEnviron() -> *mut *mut *mut c_char;
+        }
+
+        pub(crate) fn environ() -> *mut *mut c_char {
+            // SAFETY: _NSGetEnviron has no preconditions and always returns a valid
+            // pointer.
+            unsafe { *_NSGetEnviron() }
+        }
+    } else {
+        #[link(name = "c")]
+        unsafe extern "C" {
+            #[link_name = "environ"]
+            static mut ENVIRON: *mut *mut c_char;
+        }
+
+        pub(crate) fn environ() -> *mut *mut c_char {
+            // SAFETY: Reading the pointer itself is always sound.
+            unsafe { ENVIRON }
+        }
+    }
+}
+
+/// Returns the entries of the environment block, including duplicates and entries that
+/// do not contain `=`.
+///
+/// # Safety
+///
+/// The environment must not be modified while the iterator or any of the entries are
+/// alive.
+pub(crate) unsafe fn entries<'a>() -> impl Iterator<Item = &'a [u8]> {
+    let mut envp = environ();
+    core::iter::from_fn(move || {
+        // SAFETY: envp is either null or points into the null-terminated environment
+        // block whose entries are nul-terminated strings. The caller guarantees that
+        // the block is not modified.
+        unsafe {
+            if envp.is_null() || (*envp).is_null() {
+                return None;
+            }
+            let entry = *envp;
+            envp = envp.add(1);
+            Some(core::ffi::CStr::from_ptr(entry).to_bytes())
+        }
+    })
+}
@@ -0,0 +1,35 @@
+use crate::{Backend, SecureExecutionReason};
+
+pub(crate) fn requires_secure_execution() -> bool {
+    // We do not know how to determine this property on these platforms. On unix-like
+    // platforms, the conservative answer is `true`.
+    cfg!(unix)
+}
+
+pub(crate) fn has_changed_ids() -> bool {
+    false
+}
+
+#[cfg(feature = "force-env")]
+pub(crate) fn is_forced() -> bool {
+    // We do not know how to read the environment on these platforms.
+    false
+}
+
+#[cfg(all(unix, feature = "alloc"))]
+pub(crate) fn getenv(_name: &core::ffi::CStr) -> Option<alloc::ffi::CString> {
+    // We do not know how to read the environment on these platforms.
+    None
+}
+
+pub(crate) fn backend() -> Backend {
+    if cfg!(unix) {
+        Backend::AssumedTrue
+    } else {
+        Backend::AssumedFalse
+    }
+}
+
+pub(crate) fn secure_execution_reason() -> SecureExecutionReason {
+    SecureExecutionReason::Unknown
+}
@@ -0,0 +1,308 @@
+use {
+    crate::{command::private, requires_secure_execution},
+    core::ffi::{c_int, c_long, c_ulong, c_void},
+    std::{
+        io,
+        os::{fd::RawFd, unix::process::CommandExt},
+        process::Command,
+        vec::Vec,
+    },
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(any(target_os = "linux", target_os = "android"))] {
+        const SC_OPEN_MAX: c_int = 4;
+        #[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+        const SIG_SETMASK: c_int = 3;
+        #[cfg(any(target_arch = "sparc", target_arch = "sparc64"))]
+        const SIG_SETMASK: c_int = 4;
+        #[cfg(not(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "sparc",
+            target_arch = "sparc64",
+        )))]
+        const SIG_SETMASK: c_int = 2;
+        const EINVAL: c_int = 22;
+        const PR_SET_NO_NEW_PRIVS: c_int = 38;
+        const PR_CAP_AMBIENT: c_int = 47;
+        const PR_CAP_AMBIENT_CLEAR_ALL: c_int = 4;
+        #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+        const SYS_CLOSE_RANGE: c_long = 436;
+        #[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+        const CLOSE_RANGE_CLOEXEC: c_int = 4;
+    } else {
+        const SC_OPEN_MAX: c_int = 5;
+        const SIG_SETMASK: c_int = 3;
+    }
+}
+
+const F_SETFD: c_int = 2;
+const FD_CLOEXEC: c_int = 1;
+const SIG_DFL: usize = 0;
+const EPERM: c_int = 1;
+/// An upper bound for the signal numbers on all supported platforms.
+const MAX_SIGNAL: c_int = 128;
+
+#[link(name = "c")]
+unsafe extern "C" {
+    fn sysconf(name: c_int) -> c_long;
+    fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+    fn signal(sig: c_int, handler: usize) -> usize;
+    fn sigprocmask(how: c_int, set: *const c_void, old: *mut c_void) -> c_int;
+    fn setgid(gid: u32) -> c_int;
+    fn setuid(uid: u32) -> c_int;
+    safe fn getuid() -> u32;
+    safe fn geteuid() -> u32;
+    safe fn getgid() -> u32;
+    safe fn getegid() -> u32;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn prctl(option: c_int, ...) -> c_int;
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(target_arch = "mips", target_arch = "mips64")),
+    ))]
+    fn syscall(num: c_long, ...) -> c_long;
+}
+
+/// Extension methods for [`Command`] that harden the state of child processes.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait HardenCommandExt: private::Sealed {
+    /// Resets the process state that the child process would otherwise inherit if secure
+    /// execution is required.
+    ///
+    /// If [`requires_secure_execution`] returns `true` or the policy applies in all
+    /// modes, this function registers a [`CommandExt::pre_exec`] callback that runs in
+    /// the child process before the program is executed and
+    ///
+    /// - closes all file descriptors other than standard input, output, and error and
+    ///   those allowed with [`Hardening::keep_fd`],
+    /// - resets the dispositions of all signals to their defaults and unblocks all
+    ///   signals,
+    /// - on Linux and Android, clears the ambient capabilities and optionally sets
+    ///   `no_new_privs`,
+    /// - unless disabled, sets the effective user and group IDs to the real IDs.
+    ///
+    /// If any of these steps fails, spawning the child process fails. Otherwise, this
+    /// function does nothing.
+    ///
+    /// File descriptors are closed by setting their close-on-exec flag. This keeps the
+    /// file descriptors used internally by [`Command`] usable until the program is
+    /// executed. Conversely, the close-on-exec flag of allowed file descriptors is
+    /// cleared.
+    ///
+    /// ```
+    /// use {
+    ///     secure_execution::{HardenCommandExt, Hardening},
+    ///     std::process::Command,
+    /// };
+    ///
+    /// let mut cmd = Command::new("true");
+    /// cmd.harden(&Hardening::new());
+    /// ```
+    fn harden(&mut self, policy: &Hardening) -> &mut Self;
+}
+
+/// The hardening steps that [`HardenCommandExt::harden`] applies to child processes.
+#[derive(Clone, Debug)]
+pub struct Hardening {
+    keep_fds: Vec<RawFd>,
+    no_new_privs: bool,
+    drop_privileges: bool,
+    always: bool,
+}
+
+impl Default for Hardening {
+    fn default() -> Self {
+        Self {
+            keep_fds: Default::default(),
+            no_new_privs: false,
+            drop_privileges: true,
+            always: false,
+        }
+    }
+}
+
+impl Hardening {
+    /// Creates a policy that closes all file descriptors other than standard input,
+    /// output, and error, drops privileges, and only applies if secure execution is
+    /// required.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps a file descriptor open in the child process.
+    pub fn keep_fd(mut self, fd: RawFd) -> Self {
+        self.keep_fds.push(fd);
+        self
+    }
+
+    /// Sets whether `no_new_privs` is set in the child process.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn no_new_privs(mut self, no_new_privs: bool) -> Self {
+        self.no_new_privs = no_new_privs;
+        self
+    }
+
+    /// Sets whether the effective user and group IDs of the child process are set to the
+    /// real IDs.
+    pub fn drop_privileges(mut self, drop_privileges: bool) -> Self {
+        self.drop_privileges = drop_privileges;
+        self
+    }
+
+    /// Sets whether the policy also applies if secure execution is not required.
+    pub fn always(mut self, always: bool) -> Self {
+        self.always = always;
+        self
+    }
+}
+
+impl HardenCommandExt for Command {
+    fn harden(&mut self, policy: &Hardening) -> &mut Self {
+        if !policy.always && !requires_secure_execution() {
+            return self;
+        }
+        let mut keep_fds: Vec<_> = policy
+            .keep_fds
+            .iter()
+            .copied()
+            .filter(|&fd| fd > 2)
+            .collect();
+        keep_fds.sort_unstable();
+        keep_fds.dedup();
+        // SAFETY: sysconf has no preconditions. It is not async-signal-safe and must
+        // therefore be called here.
+        let open_max = match unsafe { sysconf(SC_OPEN_MAX) } {
+            n if n <= 0 => 65536,
+            n => n.min(c_int::MAX as c_long) as c_int,
+        };
+        let no_new_privs = policy.no_new_privs;
+        let drop_privileges = policy.drop_privileges;
+        let hook = move || {
+            // Everything in here must be async-signal-safe and must not allocate.
+            close_fds(&keep_fds, open_max)?;
+            reset_signals()?;
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            clear_capabilities(no_new_privs)?;
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            let _ = no_new_privs;
+            if drop_privileges {
+                drop_to_real_ids()?;
+            }
+            Ok(())
+        };
+        // SAFETY: The hook only calls async-signal-safe functions.
+        unsafe { self.pre_exec(hook) }
+    }
+}
+
+fn close_fds(keep_fds: &[RawFd], open_max: c_int) -> io::Result<()> {
+    let mut lo = 3;
+    for &fd in keep_fds {
+        set_cloexec(lo, fd - 1, open_max);
+        // SAFETY: F_SETFD takes an integer argument.
+        if unsafe { fcntl(fd, F_SETFD, 0) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        lo = fd + 1;
+    }
+    set_cloexec(lo, c_int::MAX, open_max);
+    Ok(())
+}
+
+/// Sets the close-on-exec flag of the file descriptors in `lo..=hi`.
+fn set_cloexec(lo: c_int, hi: c_int, open_max: c_int) {
+    if lo > hi {
+        return;
+    }
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(target_arch = "mips", target_arch = "mips64")),
+    ))]
+    {
+        let hi = if hi == c_int::MAX {
+            u32::MAX
+        } else {
+            hi as u32
+        };
+        // SAFETY: close_range takes two unsigned integers and flags. It is available
+        // since Linux 5.11 with CLOSE_RANGE_CLOEXEC.
+        if unsafe { syscall(SYS_CLOSE_RANGE, lo as u32, hi, CLOSE_RANGE_CLOEXEC) } == 0 {
+            return;
+        }
+    }
+    for fd in lo..=hi.min(open_max - 1) {
+        // SAFETY: F_SETFD takes an integer argument. Errors for file descriptors that are
+        // not open are expected.
+        unsafe {
+            fcntl(fd, F_SETFD, FD_CLOEXEC);
+        }
+    }
+}
+
+fn reset_signals() -> io::Result<()> {
+    for sig in 1..MAX_SIGNAL {
+        // SAFETY: Setting the default disposition is always sound. Errors for signals
+        // that cannot be changed or do not exist are expected.
+        unsafe {
+            signal(sig, SIG_DFL);
+        }
+    }
+    // The all-zero signal set is the empty set on all supported platforms and this buffer
+    // is larger than sigset_t.
+    let empty = [0u64; 16];
+    // SAFETY: empty is a valid signal set.
+    if unsafe { sigprocmask(SIG_SETMASK, empty.as_ptr().cast(), core::ptr::null_mut()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clear_capabilities(no_new_privs: bool) -> io::Result<()> {
+    // SAFETY: PR_CAP_AMBIENT takes four integer arguments.
+    let res = unsafe {
+        prctl(
+            PR_CAP_AMBIENT,
+            PR_CAP_AMBIENT_CLEAR_ALL as c_ulong,
+            0 as c_ulong,
+            0 as c_ulong,
+            0 as c_ulong,
+        )
+    };
+    // Kernels before 4.3 do not support ambient capabilities.
+    if res != 0 && io::Error::last_os_error().raw_os_error() != Some(EINVAL) {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: PR_SET_NO_NEW_PRIVS takes four integer arguments.
+    if no_new_privs
+        && unsafe {
+            prctl(
+                PR_SET_NO_NEW_PRIVS,
+                1 as c_ulong,
+                0 as c_ulong,
+                0 as c_ulong,
+                0 as c_ulong,
+            )
+        } != 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn drop_to_real_ids() -> io::Result<()> {
+    // The group must be changed first since changing the user might remove the
+    // permission to do so. After execve, the saved IDs are set to the effective IDs.
+    //
+    // SAFETY: setgid and setuid have no preconditions.
+    if unsafe { setgid(getgid()) } != 0 || unsafe { setuid(getuid()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if geteuid() != getuid() || getegid() != getgid() {
+        return Err(io::Error::from_raw_os_error(EPERM));
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use {
+    crate::{Backend, SecureExecutionReason},
+    core::ffi::{c_char, c_int},
+};
+
+#[link(name = "c")]
+unsafe extern "C" {
+    safe fn issetugid() -> c_int;
+    safe fn getuid() -> u32;
+    safe fn geteuid() -> u32;
+    safe fn getgid() -> u32;
+    safe fn getegid() -> u32;
+    #[cfg(any(feature = "force-env", feature = "alloc"))]
+    #[link_name = "getenv"]
+    fn libc_getenv(name: *const c_char) -> *mut c_char;
+}
+
+pub(crate) fn requires_secure_execution() -> bool {
+    // https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man2/issetugid.2.html
+    //     The issetugid() system call returns 1 if the process environment or memory
+    //     address space is considered ``tainted'', and returns 0 otherwise.
+    //
+    //     A process is tainted if it was created as a result of an execve(2) system
+    //     call which had either of the setuid or setgid bits set (and extra privileges
+    //     were given as a result) or if it has changed any of its real,
+    //     effective or saved user or group ID's since it began execution.
+    //
+    //     This system call exists so that library routines (eg: libc, libtermcap)
+    //     can reliably determine if it is safe to use information that was obtained
+    //     from the user, in particular the results from getenv(3) should be viewed
+    //     with suspicion if it is used to control operation.
+    //
+    // The behavior on OpenBSD and some other BSDs differs since it is not affected by id
+    // changes at runtime. Either way, both families define that this function should be
+    // used to determine the secure-execution status.
+    //
+    // https://man.openbsd.org/issetugid.2
+    //     The issetugid() function returns 1 if the process was made setuid or setgid as
+    //     the result of the last or other previous execve() system calls. Otherwise it
+    //     returns 0.
+    //
+    //     This system call exists so that library routines (inside libtermlib, libc, or
+    //     other libraries) can guarantee safe behavior when used inside setuid or setgid
+    //     programs. Some library routines may be passed insufficient information and
+    //     hence not know whether the current program was started setuid or setgid because
+    //     higher level calling code may have made changes to the uid, euid, gid, or egid.
+    //     Hence these low-level library routines are unable to determine if they are
+    //     being run with elevated or normal privileges.
+    //
+    //     In particular, it is wise to use this call to determine if a pathname returned
+    //     from a getenv() call may safely be used to open() the specified file. Quite
+    //     often this is not wise because the status of the effective uid is not known.
+    //
+    //     The issetugid() system call's result is unaffected by calls to setuid(),
+    //     setgid(), or other such calls. In case of a fork(), the child process inherits
+    //     the same status.
+    //
+    //     The status of issetugid() is only affected by execve(). If a child process
+    //     executes a new executable file, a new issetugid status will be determined. This
+    //     status is based on the existing process's uid, euid, gid, and egid permissions
+    //     and on the modes of the executable file. If the new executable file modes are
+    //     setuid or setgid, or if the existing process is executing the new image with
+    //     uid != euid or gid != egid, the new process will be considered issetugid.
+    issetugid() != 0
+}
+
+pub(crate) fn has_changed_ids() -> bool {
+    // On FreeBSD and similar platforms, issetugid also returns true if the process has
+    // changed its ids since execve.
+    issetugid() != 0
+}
+
+#[cfg(feature = "force-env")]
+pub(crate) fn is_forced() -> bool {
+    // SAFETY: The name is nul-terminated.
+    let v = unsafe { libc_getenv(crate::FORCE_ENV_NAME.as_ptr()) };
+    // SAFETY: getenv returns a nul-terminated string unless it returns null.
+    !v.is_null() && unsafe { core::ffi::CStr::from_ptr(v) }.to_bytes() == crate::FORCE_ENV_VALUE
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn getenv(name: &core::ffi::CStr) -> Option<alloc::ffi::CString> {
+    // SAFETY: name is nul-terminated.
+    let v = unsafe { libc_getenv(name.as_ptr()) };
+    // SAFETY: getenv returns a nul-terminated string unless it returns null.
+    (!v.is_null()).then(|| unsafe { core::ffi::CStr::from_ptr(v) }.into())
+}
+
+pub(crate) fn backend() -> Backend {
+    Backend::Issetugid
+}
+
+pub(crate) fn secure_execution_reason() -> SecureExecutionReason {
+    // These platforms do not record the ids at the time of execve. If the process has
+    // since changed its ids, we cannot tell why it was tainted.
+    if getuid() != geteuid() {
+        SecureExecutionReason::SetUid
+    } else if getgid() != getegid() {
+        SecureExecutionReason::SetGid
+    } else {
+        SecureExecutionReason::Unknown
+    }
+}
@@ -4,11 +4,157 @@
 //! requires secure execution.
 //!
 //! See the documentation of [`requires_secure_execution`] for details.
+//!
+//! # Features
+//!
+//! - `raw-syscalls`: On Linux and Android, use raw system calls instead of the C library
+//!   where possible. This allows using this crate in programs that do not link against
+//!   the C library. Together with `auxv::init_from_initial_stack`, this also allows using
+//!   this crate with C libraries that do not provide `getauxval`. Without this feature,
+//!   `getauxval` is always linked, even if the auxiliary vector has been recorded. This
+//!   feature has no effect on platforms other than Linux and Android and on architectures
+//!   other than `x86_64`, `aarch64`, and `riscv64`. With this feature, the auxiliary
+//!   vector is read from `/proc/self/auxv`, which processes that are not dumpable cannot
+//!   read. Such processes are assumed to require secure execution.
+//! - `alloc`: Enables `secure_getenv` on unix-like platforms.
+//! - `force-env`: Makes [`requires_secure_execution`] return `true` if the
+//!   `SECURE_EXECUTION_FORCE` environment variable is set to `1` when the property is
+//!   determined. This allows exercising code paths for secure execution without
+//!   set-user-ID binaries, for example during testing or fuzzing. The variable can never
+//!   make this function return `false`. The variable is read with `getenv` or, if the
+//!   `raw-syscalls` feature is used, from `/proc/self/environ`. This feature has no
+//!   effect on platforms where this crate does not know how to determine the property.
+//! - `std`: Enables `secure_var`, `secure_var_os`, and `secure_vars_os` that behave like
+//!   their counterparts in `std::env` but return nothing if secure execution is required,
+//!   `SecureEnv` that allows selected variables in secure execution, `SecureCommandExt`
+//!   that applies such a policy to child processes, `secure_path` that ignores `PATH` in
+//!   secure execution, and `secure_locale` that validates the locale variables in secure
+//!   execution. On unix-like platforms, it also enables `HardenCommandExt`,
+//!   `find_trusted_executable`, `scrub_environment`, `EnvAudit`, `invoking_user`,
+//!   `secure_temp_dir`, `secure_tempfile`, `secure_timezone`, and the `dirs` module, and
+//!   on Linux and Android `reexec_with_clean_environment`. This feature implies `alloc`.
+//! - `testing`: Enables the `testing` module that allows tests to force the return value
+//!   of [`requires_secure_execution`]. This feature implies `std`.
+//!
+//! The detection itself never requires `std` or `alloc`.
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(all(feature = "std", unix))]
+pub use audit::{AuditError, EnvAudit, Finding};
+#[cfg(feature = "std")]
+pub use command::{CommandEnv, SecureCommandExt};
+#[cfg(all(feature = "alloc", unix))]
+pub use env::secure_getenv;
+#[cfg(feature = "std")]
+pub use env::{secure_var, secure_var_os, secure_vars_os, SecureVarsOs};
+#[cfg(feature = "std")]
+pub use locale::{secure_locale, LocaleCategory, SecureLocale, FALLBACK_LOCALE};
+#[cfg(all(feature = "std", unix))]
+pub use path::find_trusted_executable;
+#[cfg(feature = "std")]
+pub use path::{secure_path, DEFAULT_TRUSTED_PATH};
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+pub use reexec::{reexec_with_clean_environment, ReexecError};
+#[cfg(all(feature = "std", unix))]
+pub use scrub::{scrub_environment, ScrubPolicy, ScrubReport};
+#[cfg(feature = "std")]
+pub use secure_env::{Denied, SecureEnv, Validator};
+#[cfg(all(feature = "std", unix))]
+pub use tz::{secure_timezone, SecureTimeZone, TimeZone, DEFAULT_TZDIR, DEFAULT_TZFILE};
 use {
     cfg_if::cfg_if,
     core::sync::atomic::{AtomicUsize, Ordering::Relaxed},
 };
+pub use {
+    detector::{set_detector, Detector, SetDetectorError},
+    unsafe_vars::is_unsafe_env_var,
+};
+
+#[cfg(all(feature = "std", unix))]
+mod audit;
+#[cfg(feature = "std")]
+mod command;
+mod detector;
+#[cfg(feature = "alloc")]
+mod env;
+#[cfg(all(feature = "std", unix))]
+mod environ;
+#[cfg(feature = "std")]
+mod locale;
+#[cfg(feature = "std")]
+mod path;
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+mod reexec;
+#[cfg(all(feature = "std", unix))]
+mod scrub;
+#[cfg(feature = "std")]
+mod secure_env;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(all(feature = "std", unix))]
+mod tz;
+pub mod unsafe_vars;
+
+cfg_if! {
+    if #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+    ))] {
+        pub mod auxv;
+        #[cfg(feature = "std")]
+        pub mod dirs;
+        #[cfg(feature = "std")]
+        mod harden;
+        mod linux;
+        #[cfg(feature = "std")]
+        mod passwd;
+        #[cfg(feature = "std")]
+        mod temp;
+        #[cfg(feature = "std")]
+        pub use passwd::{invoking_user, Group, InvokingUser, InvokingUserError};
+        #[cfg(feature = "std")]
+        pub use temp::{secure_temp_dir, secure_tempfile};
+        use linux as sys;
+        #[cfg(feature = "std")]
+        pub use harden::{HardenCommandExt, Hardening};
+    } else if #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris",
+    ))] {
+        #[cfg(feature = "std")]
+        pub mod dirs;
+        #[cfg(feature = "std")]
+        mod harden;
+        mod issetugid;
+        #[cfg(feature = "std")]
+        mod passwd;
+        #[cfg(feature = "std")]
+        mod temp;
+        #[cfg(feature = "std")]
+        pub use passwd::{invoking_user, Group, InvokingUser, InvokingUserError};
+        #[cfg(feature = "std")]
+        pub use temp::{secure_temp_dir, secure_tempfile};
+        use issetugid as sys;
+        #[cfg(feature = "std")]
+        pub use harden::{HardenCommandExt, Hardening};
+    } else {
+        mod fallback;
+        use fallback as sys;
+    }
+}
 
 /// Returns whether the running executable requires secure execution.
 ///
@@ -29,11 +175,30 @@ use {
 /// > In particular, it is wise to use \[this property] to determine if a pathname
 /// > returned from a `getenv()` call may safely be used to `open()` the specified file.
 ///
-/// How this function determines this property depends on the `target_os` value.
+/// If a custom detector has been installed with [`set_detector`], its return value is
+/// used. Otherwise, how this function determines this property depends on the
+/// `target_os` value.
 ///
 /// - If `target_os` is one of `linux` or `android`, the `AT_SECURE` value from
 ///   `getauxval` is used. See [`getauxval(3)`] for details.
 ///
+///   If the auxiliary vector has been recorded with
+///   [`auxv::init_from_initial_stack`] or [`auxv::init_from_environ`], the value is read
+///   from the recorded vector instead.
+///
+///   Otherwise, if the `raw-syscalls` feature is enabled and `target_arch` is one of
+///   `x86_64`, `aarch64`, or `riscv64`, this value is read from `/proc/self/auxv` without
+///   calling into the C library. If that file cannot be read, the entry is treated as not
+///   present. However, if reading fails with `EACCES`, which happens if the process is
+///   not dumpable, for example because it executed a set-user-ID or set-group-ID binary
+///   or changed its ids, this function returns `true`.
+///
+///   If the `AT_SECURE` entry is not present, for example because of an unusual kernel or
+///   an emulator such as qemu-user, this function returns `true` if the real, effective,
+///   and saved user or group IDs returned by `getresuid` and `getresgid` differ, or if
+///   those functions fail. Use [`backend`] to determine which of these mechanisms is
+///   used.
+///
 ///   [`getauxval(3)`]: https://man7.org/linux/man-pages/man3/getauxval.3.html
 ///
 /// - Otherwise, if `target_os` is one of `macos`, `ios`, `watchos`, `tvos`, `visionos`,
@@ -46,135 +211,355 @@ use {
 ///
 ///   Note that, on FreeBSD and other operating systems using the same model, the return
 ///   value of `issetugid` can change at runtime. But this function always caches the
-///   result when it is called for the first time.
+///   result when it is called for the first time. Use
+///   [`requires_secure_execution_uncached`] or [`refresh_secure_execution`] to observe
+///   such changes.
 ///
 ///   [OpenBSD]: https://man.openbsd.org/issetugid.2
 ///   [FreeBSD]: https://man.freebsd.org/cgi/man.cgi?query=issetugid
 ///
-/// - Otherwise, if `cfg(unix)`, this function always returns `true`. As of this
+/// - Otherwise, if `cfg(unix)`, this function always returns `true`. Use
+///   [`secure_execution`] to distinguish this guess from a real answer. As of this
 ///   writing, this affects the following `target_os` values:
 ///
 ///   `aix`, `emscripten`, `espidf`, `fuchsia`, `haiku`, `horizon`, `hurd`, `l4re`, `nto`,
 ///   `nuttx`, `redox`, `rtems`, `vita`, and `vxworks`
 ///
-/// - Otherwise, this function always returns `false`. As of this writing, this affects
+///   Platform integrators that know how to determine this property on such a platform
+///   can install a custom detector with [`set_detector`].
+///
+/// - Otherwise, this function always returns `false`. Use [`secure_execution`] to
+///   distinguish this guess from a real answer. As of this writing, this affects
 ///   the following `target_os` values:
 ///
 ///   `cuda`, `hermit`, `psp`, `solid_asp3`, `teeos`, `trusty`, `uefi`, `wasi`, `windows`,
 ///   `xous`, and `zkvm`
 #[inline(always)]
 pub fn requires_secure_execution() -> bool {
-    const FALSE: usize = 0;
-    const TRUE: usize = 1;
-    const TODO: usize = 2;
-    static STATE: AtomicUsize = AtomicUsize::new(TODO);
-
+    #[cfg(feature = "testing")]
+    if let Some(value) = testing::get() {
+        return value;
+    }
     match STATE.load(Relaxed) {
         FALSE => false,
         TRUE => true,
+        _ => refresh_secure_execution(),
+    }
+}
+
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+const TODO: usize = 2;
+static STATE: AtomicUsize = AtomicUsize::new(TODO);
+
+/// Returns whether the running executable requires secure execution without using the
+/// cache.
+///
+/// This function determines the property in the same way as
+/// [`requires_secure_execution`] but neither reads nor updates the cache used by that
+/// function. This is useful on platforms where the property can change at runtime, for
+/// example on FreeBSD after a call to `setuid`.
+///
+/// On Linux and Android, the `AT_SECURE` value never changes at runtime. Use
+/// [`requires_secure_execution_with`] with [`Semantics::Tainted`] to detect changes of
+/// the user and group IDs after `execve` instead.
+pub fn requires_secure_execution_uncached() -> bool {
+    #[cfg(feature = "testing")]
+    if let Some(value) = testing::get() {
+        return value;
+    }
+    detect()
+}
+
+fn detect() -> bool {
+    let required = match detector::detector() {
+        Some(detector) => detector(),
+        _ => sys::requires_secure_execution(),
+    };
+    #[cfg(feature = "testing")]
+    testing::check_release(required);
+    #[cfg(feature = "force-env")]
+    let required = required || sys::is_forced();
+    required
+}
+
+#[cfg(feature = "force-env")]
+const FORCE_ENV_NAME: &core::ffi::CStr = c"SECURE_EXECUTION_FORCE";
+#[cfg(feature = "force-env")]
+const FORCE_ENV_VALUE: &[u8] = b"1";
+
+/// Determines whether the running executable requires secure execution and updates the
+/// cache.
+///
+/// This function determines the property like [`requires_secure_execution_uncached`]
+/// and stores the result in the cache used by [`requires_secure_execution`]. All later
+/// calls of that function, including calls in other threads, return the new value. The
+/// cache of [`secure_execution_reason`] is invalidated.
+///
+/// Calls of [`requires_secure_execution`] that race with this function might return the
+/// old value.
+///
+/// If an override of the `testing` module is active, this function returns the
+/// overridden value like [`requires_secure_execution_uncached`]. The cache is still
+/// updated with the real value.
+pub fn refresh_secure_execution() -> bool {
+    let state = detect();
+    STATE.store(state as usize, Relaxed);
+    REASON.store(REASON_TODO, Relaxed);
+    #[cfg(feature = "testing")]
+    if let Some(value) = testing::get() {
+        return value;
+    }
+    state
+}
+
+/// The semantics used by [`requires_secure_execution_with`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Semantics {
+    /// Secure execution is required if the process was started in a way that requires
+    /// it. This is the behavior of [`requires_secure_execution`].
+    ExecTime,
+    /// Secure execution is required if it was required at the time of `execve` or if the
+    /// process has changed its real, effective, or saved user or group IDs since then.
+    ///
+    /// This is the model used by `issetugid` on FreeBSD, macOS, and similar platforms.
+    Tainted,
+}
+
+/// Returns whether the running executable requires secure execution under the given
+/// semantics.
+///
+/// With [`Semantics::ExecTime`], this function returns the same value as
+/// [`requires_secure_execution`].
+///
+/// With [`Semantics::Tainted`], this function additionally returns `true` if the process
+/// is considered tainted. Unlike the exec-time property, this property can change at
+/// runtime and is therefore not cached. How this property is determined depends on the
+/// `target_os` value.
+///
+/// - If `target_os` is one of `linux` or `android`, the `AT_UID`, `AT_EUID`, `AT_GID`,
+///   and `AT_EGID` values from the auxiliary vector are compared with the current ids
+///   returned by `getresuid` and `getresgid`. The process is tainted if any of the ids
+///   differ or if they cannot be determined.
+///
+/// - Otherwise, if [`requires_secure_execution`] uses `issetugid`, the current return
+///   value of `issetugid` is used. Note that, on OpenBSD and other operating systems
+///   using the same model, this value does not change at runtime.
+///
+/// - Otherwise, no process is considered tainted.
+///
+/// This is useful for daemons that start as root and later call `setuid` to drop their
+/// privileges.
+pub fn requires_secure_execution_with(semantics: Semantics) -> bool {
+    match semantics {
+        Semantics::ExecTime => requires_secure_execution(),
+        Semantics::Tainted => requires_secure_execution() || sys::has_changed_ids(),
+    }
+}
+
+/// The reason why the running executable requires secure execution.
+///
+/// See the documentation of [`secure_execution_reason`] for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SecureExecutionReason {
+    /// Secure execution is not required.
+    NotRequired,
+    /// The real and effective user IDs differ, usually because the executable is a
+    /// set-user-ID binary.
+    SetUid,
+    /// The real and effective group IDs differ, usually because the executable is a
+    /// set-group-ID binary.
+    SetGid,
+    /// The process gained capabilities by executing a binary that has file
+    /// capabilities.
+    FileCapabilities,
+    /// Secure execution is required for another reason, for example because of a Linux
+    /// Security Module, or the reason cannot be determined on this platform.
+    Unknown,
+    /// Secure execution was forced with the `SECURE_EXECUTION_FORCE` environment
+    /// variable. See the `force-env` feature.
+    Forced,
+}
+
+impl SecureExecutionReason {
+    const VALUES: [Self; 6] = [
+        Self::NotRequired,
+        Self::SetUid,
+        Self::SetGid,
+        Self::FileCapabilities,
+        Self::Unknown,
+        Self::Forced,
+    ];
+}
+
+/// Returns why the running executable requires secure execution.
+///
+/// This function returns [`SecureExecutionReason::NotRequired`] if and only if
+/// [`requires_secure_execution`] returns `false`. Like that function, it caches its
+/// result when it is called for the first time. The cache is invalidated by
+/// [`refresh_secure_execution`].
+///
+/// How this function determines the reason depends on the `target_os` value.
+///
+/// - If `target_os` is one of `linux` or `android`, the `AT_UID`, `AT_EUID`, `AT_GID`,
+///   and `AT_EGID` values from `getauxval` are compared. These values describe the
+///   process at the time of `execve` and are therefore not affected by later calls to
+///   `setuid` and similar functions. If they do not differ but the process has
+///   permitted capabilities even though its real user ID is not 0, the reason is
+///   [`SecureExecutionReason::FileCapabilities`].
+///
+/// - Otherwise, if `requires_secure_execution` uses `issetugid`, the current real and
+///   effective user and group IDs are compared.
+///
+/// - Otherwise, no further information is available.
+///
+/// If none of the checks applies, for example because the `AT_SECURE` flag was set by a
+/// Linux Security Module, the reason is [`SecureExecutionReason::Unknown`]. If both the
+/// user and the group IDs differ, the reason is [`SecureExecutionReason::SetUid`].
+pub fn secure_execution_reason() -> SecureExecutionReason {
+    #[cfg(feature = "testing")]
+    if let Some(value) = testing::get() {
+        return match value {
+            true => SecureExecutionReason::Unknown,
+            false => SecureExecutionReason::NotRequired,
+        };
+    }
+    match SecureExecutionReason::VALUES.get(REASON.load(Relaxed)) {
+        Some(reason) => *reason,
         _ => {
-            let state = requires_secure_execution_uncached();
-            STATE.store(state as usize, Relaxed);
-            state
+            let reason = secure_execution_reason_uncached();
+            REASON.store(reason as usize, Relaxed);
+            reason
         }
     }
 }
 
-fn requires_secure_execution_uncached() -> bool {
-    cfg_if! {
-        if #[cfg(any(
-            target_os = "linux",
-            target_os = "android",
-        ))] {
-            // https://man7.org/linux/man-pages/man3/getauxval.3.html
-            //     AT_SECURE
-            //            Has a nonzero value if this executable should be treated
-            //            securely.  Most commonly, a nonzero value indicates that
-            //            the process is executing a set-user-ID or set-group-ID
-            //            binary (so that its real and effective UIDs or GIDs differ
-            //            from one another), or that it gained capabilities by
-            //            executing a binary file that has capabilities (see
-            //            capabilities(7)).  Alternatively, a nonzero value may be
-            //            triggered by a Linux Security Module.  When this value is
-            //            nonzero, the dynamic linker disables the use of certain
-            //            environment variables (see ld-linux.so(8)) and glibc
-            //            changes other aspects of its behavior.  (See also
-            //            secure_getenv(3).)
-            use core::ffi::c_ulong;
-            #[link(name = "c")]
-            unsafe extern {
-                safe fn getauxval(ty: c_ulong) -> c_ulong;
-            }
-            const AT_SECURE: c_ulong = 23;
-            getauxval(AT_SECURE) != 0
-        } else if #[cfg(any(
-            target_os = "macos",
-            target_os = "ios",
-            target_os = "watchos",
-            target_os = "tvos",
-            target_os = "visionos",
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "illumos",
-            target_os = "netbsd",
-            target_os = "openbsd",
-            target_os = "solaris",
-        ))] {
-            // https://developer.apple.com/library/archive/documentation/System/Conceptual/ManPages_iPhoneOS/man2/issetugid.2.html
-            //     The issetugid() system call returns 1 if the process environment or memory
-            //     address space is considered ``tainted'', and returns 0 otherwise.
-            //
-            //     A process is tainted if it was created as a result of an execve(2) system
-            //     call which had either of the setuid or setgid bits set (and extra privileges
-            //     were given as a result) or if it has changed any of its real,
-            //     effective or saved user or group ID's since it began execution.
-            //
-            //     This system call exists so that library routines (eg: libc, libtermcap)
-            //     can reliably determine if it is safe to use information that was obtained
-            //     from the user, in particular the results from getenv(3) should be viewed
-            //     with suspicion if it is used to control operation.
-            //
-            // The behavior on OpenBSD and some other BSDs differs since it is not affected by id
-            // changes at runtime. Either way, both families define that this function should be
-            // used to determine the secure-execution status.
-            //
-            // https://man.openbsd.org/issetugid.2
-            //     The issetugid() function returns 1 if the process was made setuid or setgid as
-            //     the result of the last or other previous execve() system calls. Otherwise it
-            //     returns 0.
-            //
-            //     This system call exists so that library routines (inside libtermlib, libc, or
-            //     other libraries) can guarantee safe behavior when used inside setuid or setgid
-            //     programs. Some library routines may be passed insufficient information and
-            //     hence not know whether the current program was started setuid or setgid because
-            //     higher level calling code may have made changes to the uid, euid, gid, or egid.
-            //     Hence these low-level library routines are unable to determine if they are
-            //     being run with elevated or normal privileges.
-            //
-            //     In particular, it is wise to use this call to determine if a pathname returned
-            //     from a getenv() call may safely be used to open() the specified file. Quite
-            //     often this is not wise because the status of the effective uid is not known.
-            //
-            //     The issetugid() system call's result is unaffected by calls to setuid(),
-            //     setgid(), or other such calls. In case of a fork(), the child process inherits
-            //     the same status.
-            //
-            //     The status of issetugid() is only affected by execve(). If a child process
-            //     executes a new executable file, a new issetugid status will be determined. This
-            //     status is based on the existing process's uid, euid, gid, and egid permissions
-            //     and on the modes of the executable file. If the new executable file modes are
-            //     setuid or setgid, or if the existing process is executing the new image with
-            //     uid != euid or gid != egid, the new process will be considered issetugid.
-            use core::ffi::c_int;
-            #[link(name = "c")]
-            unsafe extern {
-                safe fn issetugid() -> c_int;
-            }
-            issetugid() != 0
-        } else if #[cfg(unix)] {
-            true
-        } else {
-            false
+const REASON_TODO: usize = usize::MAX;
+static REASON: AtomicUsize = AtomicUsize::new(REASON_TODO);
+
+fn secure_execution_reason_uncached() -> SecureExecutionReason {
+    if !requires_secure_execution() {
+        return SecureExecutionReason::NotRequired;
+    }
+    let reason = match backend() {
+        Backend::Custom => SecureExecutionReason::Unknown,
+        _ => sys::secure_execution_reason(),
+    };
+    #[cfg(feature = "force-env")]
+    if reason == SecureExecutionReason::Unknown && sys::is_forced() {
+        return SecureExecutionReason::Forced;
+    }
+    reason
+}
+
+/// The mechanism used to determine whether the running executable requires secure
+/// execution.
+///
+/// See the documentation of [`backend`] for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// The `AT_SECURE` entry of the auxiliary vector is used.
+    Getauxval,
+    /// The `AT_SECURE` entry of the auxiliary vector is not present and the real,
+    /// effective, and saved user and group IDs are compared instead.
+    ///
+    /// With the `raw-syscalls` feature, this is also returned if `/proc/self/auxv` cannot
+    /// be read because the process is not dumpable. In that case,
+    /// [`requires_secure_execution`] returns `true` without comparing the ids.
+    Credentials,
+    /// The return value of `issetugid` is used.
+    Issetugid,
+    /// This crate does not know how to determine the property on this platform and
+    /// assumes that secure execution is required.
+    AssumedTrue,
+    /// This crate does not know how to determine the property on this platform and
+    /// assumes that secure execution is not required.
+    AssumedFalse,
+    /// A custom detector installed with [`set_detector`] is used.
+    Custom,
+}
+
+impl Backend {
+    const VALUES: [Self; 6] = [
+        Self::Getauxval,
+        Self::Credentials,
+        Self::Issetugid,
+        Self::AssumedTrue,
+        Self::AssumedFalse,
+        Self::Custom,
+    ];
+}
+
+/// Returns the mechanism that [`requires_secure_execution`] uses.
+///
+/// If this function returns [`Backend::AssumedTrue`] or [`Backend::AssumedFalse`], the
+/// return value of [`requires_secure_execution`] is a guess. See the documentation of
+/// that function for the affected platforms.
+///
+/// Like that function, this function caches its result when it is called for the first
+/// time.
+pub fn backend() -> Backend {
+    const TODO: usize = usize::MAX;
+    static BACKEND: AtomicUsize = AtomicUsize::new(TODO);
+
+    match Backend::VALUES.get(BACKEND.load(Relaxed)) {
+        Some(backend) => *backend,
+        _ => {
+            let backend = match detector::detector() {
+                Some(_) => Backend::Custom,
+                _ => sys::backend(),
+            };
+            BACKEND.store(backend as usize, Relaxed);
+            backend
+        }
+    }
+}
+
+/// Whether the running executable requires secure execution.
+///
+/// See the documentation of [`secure_execution`] for details.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SecureExecution {
+    /// Secure execution is required.
+    Required,
+    /// Secure execution is not required.
+    NotRequired,
+    /// This crate does not know how to determine whether secure execution is required
+    /// on this platform.
+    Unknown {
+        /// The value returned by [`requires_secure_execution`].
+        assumed: bool,
+    },
+}
+
+impl SecureExecution {
+    /// Returns the value that [`requires_secure_execution`] returns for this state.
+    pub fn is_required(self) -> bool {
+        match self {
+            SecureExecution::Required => true,
+            SecureExecution::NotRequired => false,
+            SecureExecution::Unknown { assumed } => assumed,
+        }
+    }
+}
+
+/// Returns whether the running executable requires secure execution, distinguishing
+/// real answers from guesses.
+///
+/// This function returns [`SecureExecution::Unknown`] if [`backend`] returns
+/// [`Backend::AssumedTrue`] or [`Backend::AssumedFalse`]. Otherwise, it returns
+/// [`SecureExecution::Required`] or [`SecureExecution::NotRequired`] depending on the
+/// return value of [`requires_secure_execution`].
+pub fn secure_execution() -> SecureExecution {
+    let required = requires_secure_execution();
+    match backend() {
+        Backend::AssumedTrue | Backend::AssumedFalse => {
+            SecureExecution::Unknown { assumed: required }
         }
+        _ if required => SecureExecution::Required,
+        _ => SecureExecution::NotRequired,
     }
 }
@@ -0,0 +1,129 @@
+use {
+    super::{CapUserData, CapUserHeader},
+    crate::auxv::Auxv,
+    core::ffi::{c_char, c_int, c_ulong, c_void, CStr},
+};
+
+#[link(name = "c")]
+unsafe extern "C" {
+    #[link_name = "getauxval"]
+    safe fn libc_getauxval(ty: c_ulong) -> c_ulong;
+    #[cfg_attr(target_os = "android", link_name = "__errno")]
+    #[cfg_attr(not(target_os = "android"), link_name = "__errno_location")]
+    safe fn errno_location() -> *mut c_int;
+    #[link_name = "open"]
+    fn libc_open(path: *const c_char, flags: c_int, ...) -> c_int;
+    #[link_name = "read"]
+    fn libc_read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+    #[link_name = "close"]
+    fn libc_close(fd: c_int) -> c_int;
+    #[link_name = "capget"]
+    fn libc_capget(hdr: *mut CapUserHeader, data: *mut CapUserData) -> c_int;
+    #[cfg(any(feature = "force-env", feature = "alloc"))]
+    #[link_name = "getenv"]
+    fn libc_getenv(name: *const c_char) -> *mut c_char;
+    #[link_name = "getresuid"]
+    fn libc_getresuid(ruid: *mut u32, euid: *mut u32, suid: *mut u32) -> c_int;
+    #[link_name = "getresgid"]
+    fn libc_getresgid(rgid: *mut u32, egid: *mut u32, sgid: *mut u32) -> c_int;
+}
+
+const ENOENT: c_int = 2;
+
+fn errno() -> c_int {
+    // SAFETY: errno_location returns a valid pointer to the thread-local errno.
+    unsafe { *errno_location() }
+}
+
+fn set_errno(errno: c_int) {
+    // SAFETY: errno_location returns a valid pointer to the thread-local errno.
+    unsafe {
+        *errno_location() = errno;
+    }
+}
+
+pub(crate) fn getauxval(ty: c_ulong) -> Option<c_ulong> {
+    set_errno(0);
+    let value = libc_getauxval(ty);
+    if value == 0 && errno() == ENOENT {
+        return None;
+    }
+    Some(value)
+}
+
+pub(crate) fn auxv_denied() -> bool {
+    false
+}
+
+pub(crate) fn auxv_snapshot() -> Auxv {
+    Auxv::from_fn(getauxval)
+}
+
+pub(crate) fn open(path: &CStr, flags: c_int) -> Result<c_int, c_int> {
+    // SAFETY: path is nul-terminated.
+    let fd = unsafe { libc_open(path.as_ptr(), flags) };
+    match fd {
+        ..0 => Err(errno()),
+        _ => Ok(fd),
+    }
+}
+
+pub(crate) fn read(fd: c_int, buf: &mut [u8]) -> Result<usize, c_int> {
+    // SAFETY: buf is a valid buffer of the given length.
+    let n = unsafe { libc_read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    match n {
+        ..0 => Err(errno()),
+        _ => Ok(n as usize),
+    }
+}
+
+pub(crate) fn close(fd: c_int) {
+    // SAFETY: The caller owns fd.
+    unsafe {
+        libc_close(fd);
+    }
+}
+
+pub(crate) fn capget(hdr: &mut CapUserHeader, data: &mut [CapUserData; 2]) -> Result<(), c_int> {
+    // SAFETY: hdr and data have the layout required by _LINUX_CAPABILITY_VERSION_3.
+    match unsafe { libc_capget(hdr, data.as_mut_ptr()) } {
+        0 => Ok(()),
+        _ => Err(errno()),
+    }
+}
+
+pub(crate) fn getresuid() -> Result<[u32; 3], c_int> {
+    let mut ids = [0; 3];
+    let [r, e, s] = &mut ids;
+    // SAFETY: The pointers are valid for writes.
+    match unsafe { libc_getresuid(r, e, s) } {
+        0 => Ok(ids),
+        _ => Err(errno()),
+    }
+}
+
+pub(crate) fn getresgid() -> Result<[u32; 3], c_int> {
+    let mut ids = [0; 3];
+    let [r, e, s] = &mut ids;
+    // SAFETY: The pointers are valid for writes.
+    match unsafe { libc_getresgid(r, e, s) } {
+        0 => Ok(ids),
+        _ => Err(errno()),
+    }
+}
+
+#[cfg(feature = "force-env")]
+pub(crate) fn env_var_is(name: &CStr, value: &[u8]) -> bool {
+    // SAFETY: name is nul-terminated.
+    let v = unsafe { libc_getenv(name.as_ptr()) };
+    // SAFETY: getenv returns a nul-terminated string unless it returns null.
+    !v.is_null() && unsafe { CStr::from_ptr(v) }.to_bytes() == value
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn getenv(name: &CStr) -> Option<alloc::ffi::CString> {
+    // SAFETY: name is nul-terminated.
+    let v = unsafe { libc_getenv(name.as_ptr()) };
+    // SAFETY: getenv returns a nul-terminated string unless it returns null.
+    (!v.is_null()).then(|| unsafe { CStr::from_ptr(v) }.into())
+}
@@ -0,0 +1,168 @@
+use {
+    crate::{
+        auxv::{getauxval, Auxv, AT_SECURE},
+        Backend, SecureExecutionReason,
+    },
+    cfg_if::cfg_if,
+    core::ffi::c_int,
+};
+
+cfg_if! {
+    if #[cfg(all(
+        feature = "raw-syscalls",
+        any(
+            target_arch = "x86_64",
+            target_arch = "aarch64",
+            target_arch = "riscv64",
+        ),
+    ))] {
+        pub(crate) mod syscall;
+        pub(crate) use syscall as sys;
+    } else {
+        pub(crate) mod ffi;
+        pub(crate) use ffi as sys;
+    }
+}
+
+pub(crate) const EINTR: c_int = 4;
+pub(crate) const O_RDONLY: c_int = 0;
+pub(crate) const O_CLOEXEC: c_int = 0o2000000;
+
+const _LINUX_CAPABILITY_VERSION_3: u32 = 0x20080522;
+const _LINUX_CAPABILITY_U32S_3: usize = 2;
+
+#[repr(C)]
+pub(crate) struct CapUserHeader {
+    version: u32,
+    pid: c_int,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+pub(crate) struct CapUserData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+pub(crate) fn requires_secure_execution() -> bool {
+    // https://man7.org/linux/man-pages/man3/getauxval.3.html
+    //     AT_SECURE
+    //            Has a nonzero value if this executable should be treated
+    //            securely.  Most commonly, a nonzero value indicates that
+    //            the process is executing a set-user-ID or set-group-ID
+    //            binary (so that its real and effective UIDs or GIDs differ
+    //            from one another), or that it gained capabilities by
+    //            executing a binary file that has capabilities (see
+    //            capabilities(7)).  Alternatively, a nonzero value may be
+    //            triggered by a Linux Security Module.  When this value is
+    //            nonzero, the dynamic linker disables the use of certain
+    //            environment variables (see ld-linux.so(8)) and glibc
+    //            changes other aspects of its behavior.  (See also
+    //            secure_getenv(3).)
+    //
+    // If the entry is not present, for example because the kernel or an emulator such as
+    // qemu-user does not provide it, or because /proc/self/auxv cannot be read, fall back
+    // to comparing the ids of the process.
+    //
+    // /proc/self/auxv is only readable by the owner of the process. If the process is not
+    // dumpable, for example because it executed a set-user-ID or set-group-ID binary or
+    // changed its ids, the owner is root and the file cannot be read. In that case, the
+    // ids might already be equal again or AT_SECURE might have been set by a Linux
+    // Security Module, so assume that secure execution is required.
+    match getauxval(AT_SECURE) {
+        Some(secure) => secure != 0,
+        None if sys::auxv_denied() => true,
+        None => Credentials::get().is_none_or(|c| c.differ()),
+    }
+}
+
+pub(crate) fn has_changed_ids() -> bool {
+    // At the time of execve, the saved ids are set to the effective ids. If the ids at
+    // that time cannot be determined, for example because /proc/self/auxv became
+    // unreadable after the process changed its ids, the process is considered tainted.
+    // The current ids cannot be used instead since they are all equal after a call to
+    // setuid that drops privileges.
+    let auxv = Auxv::snapshot();
+    let (Some(uid), Some(euid), Some(gid), Some(egid)) =
+        (auxv.uid(), auxv.euid(), auxv.gid(), auxv.egid())
+    else {
+        return true;
+    };
+    let Some(c) = Credentials::get() else {
+        return true;
+    };
+    c.uids != [uid, euid, euid] || c.gids != [gid, egid, egid]
+}
+
+#[cfg(feature = "force-env")]
+pub(crate) fn is_forced() -> bool {
+    sys::env_var_is(crate::FORCE_ENV_NAME, crate::FORCE_ENV_VALUE)
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn getenv(name: &core::ffi::CStr) -> Option<alloc::ffi::CString> {
+    sys::getenv(name)
+}
+
+pub(crate) fn backend() -> Backend {
+    match getauxval(AT_SECURE) {
+        Some(_) => Backend::Getauxval,
+        None => Backend::Credentials,
+    }
+}
+
+pub(crate) fn secure_execution_reason() -> SecureExecutionReason {
+    // AT_UID, AT_EUID, AT_GID, and AT_EGID contain the ids at the time of the execve call
+    // and are therefore not affected by later calls to setuid and similar functions. If
+    // they are not available, use the current ids instead.
+    let auxv = Auxv::snapshot();
+    let (uids, gids) = match (auxv.uid(), auxv.euid(), auxv.gid(), auxv.egid()) {
+        (Some(uid), Some(euid), Some(gid), Some(egid)) => ([uid, euid], [gid, egid]),
+        _ => match Credentials::get() {
+            Some(c) => ([c.uids[0], c.uids[1]], [c.gids[0], c.gids[1]]),
+            _ => return SecureExecutionReason::Unknown,
+        },
+    };
+    if uids[0] != uids[1] {
+        SecureExecutionReason::SetUid
+    } else if gids[0] != gids[1] {
+        SecureExecutionReason::SetGid
+    } else if uids[0] != 0 && has_permitted_capabilities() {
+        // The ids did not change but an unprivileged user has capabilities. The only way
+        // for this to happen during execve is via file capabilities.
+        SecureExecutionReason::FileCapabilities
+    } else {
+        SecureExecutionReason::Unknown
+    }
+}
+
+fn has_permitted_capabilities() -> bool {
+    let mut hdr = CapUserHeader {
+        version: _LINUX_CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapUserData::default(); _LINUX_CAPABILITY_U32S_3];
+    sys::capget(&mut hdr, &mut data).is_ok() && data.iter().any(|d| d.permitted != 0)
+}
+
+/// The real, effective, and saved user and group IDs of the process.
+struct Credentials {
+    uids: [u32; 3],
+    gids: [u32; 3],
+}
+
+impl Credentials {
+    fn get() -> Option<Self> {
+        Some(Self {
+            uids: sys::getresuid().ok()?,
+            gids: sys::getresgid().ok()?,
+        })
+    }
+
+    fn differ(&self) -> bool {
+        let [ruid, euid, suid] = self.uids;
+        let [rgid, egid, sgid] = self.gids;
+        ruid != euid || ruid != suid || rgid != egid || rgid != sgid
+    }
+}
@@ -0,0 +1,233 @@
+use {
+    super::{CapUserData, CapUserHeader},
+    crate::auxv::{self, Auxv},
+    core::{
+        arch::asm,
+        ffi::{c_int, c_ulong, CStr},
+    },
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        const SYS_READ: usize = 0;
+        const SYS_CLOSE: usize = 3;
+        const SYS_GETRESUID: usize = 118;
+        const SYS_GETRESGID: usize = 120;
+        const SYS_CAPGET: usize = 125;
+        const SYS_OPENAT: usize = 257;
+    } else {
+        const SYS_OPENAT: usize = 56;
+        const SYS_CLOSE: usize = 57;
+        const SYS_READ: usize = 63;
+        const SYS_CAPGET: usize = 90;
+        const SYS_GETRESUID: usize = 148;
+        const SYS_GETRESGID: usize = 150;
+    }
+}
+
+const AT_FDCWD: c_int = -100;
+const EACCES: c_int = 13;
+
+unsafe fn syscall3(nr: usize, a0: usize, a1: usize, a2: usize) -> isize {
+    let ret;
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "x86_64")] {
+            // SAFETY: The caller upholds the contract of the system call.
+            unsafe {
+                asm!(
+                    "syscall",
+                    inlateout("rax") nr as isize => ret,
+                    in("rdi") a0,
+                    in("rsi") a1,
+                    in("rdx") a2,
+                    lateout("rcx") _,
+                    lateout("r11") _,
+                    options(nostack),
+                );
+            }
+        } else if #[cfg(target_arch = "aarch64")] {
+            // SAFETY: The caller upholds the contract of the system call.
+            unsafe {
+                asm!(
+                    "svc 0",
+                    in("x8") nr,
+                    inlateout("x0") a0 as isize => ret,
+                    in("x1") a1,
+                    in("x2") a2,
+                    options(nostack),
+                );
+            }
+        } else {
+            // SAFETY: The caller upholds the contract of the system call.
+            unsafe {
+                asm!(
+                    "ecall",
+                    in("a7") nr,
+                    inlateout("a0") a0 as isize => ret,
+                    in("a1") a1,
+                    in("a2") a2,
+                    options(nostack),
+                );
+            }
+        }
+    }
+    ret
+}
+
+fn result(ret: isize) -> Result<usize, c_int> {
+    match ret {
+        -4095..0 => Err(-ret as c_int),
+        _ => Ok(ret as usize),
+    }
+}
+
+pub(crate) fn getauxval(ty: c_ulong) -> Option<c_ulong> {
+    auxv::entries()?.find(|e| e.key == ty).map(|e| e.value)
+}
+
+pub(crate) fn auxv_denied() -> bool {
+    matches!(auxv::read_entries(), Err(EACCES))
+}
+
+pub(crate) fn auxv_snapshot() -> Auxv {
+    let entries = auxv::entries();
+    Auxv::from_fn(|ty| entries.clone()?.find(|e| e.key == ty).map(|e| e.value))
+}
+
+pub(crate) fn open(path: &CStr, flags: c_int) -> Result<c_int, c_int> {
+    // SAFETY: path is nul-terminated.
+    let ret = unsafe {
+        syscall3(
+            SYS_OPENAT,
+            AT_FDCWD as usize,
+            path.as_ptr() as usize,
+            flags as usize,
+        )
+    };
+    result(ret).map(|fd| fd as c_int)
+}
+
+pub(crate) fn read(fd: c_int, buf: &mut [u8]) -> Result<usize, c_int> {
+    // SAFETY: buf is a valid buffer of the given length.
+    let ret = unsafe { syscall3(SYS_READ, fd as usize, buf.as_mut_ptr() as usize, buf.len()) };
+    result(ret)
+}
+
+pub(crate) fn close(fd: c_int) {
+    // SAFETY: The caller owns fd.
+    unsafe {
+        syscall3(SYS_CLOSE, fd as usize, 0, 0);
+    }
+}
+
+pub(crate) fn capget(hdr: &mut CapUserHeader, data: &mut [CapUserData; 2]) -> Result<(), c_int> {
+    // SAFETY: hdr and data have the layout required by _LINUX_CAPABILITY_VERSION_3.
+    let ret = unsafe {
+        syscall3(
+            SYS_CAPGET,
+            hdr as *mut CapUserHeader as usize,
+            data.as_mut_ptr() as usize,
+            0,
+        )
+    };
+    result(ret).map(drop)
+}
+
+pub(crate) fn getresuid() -> Result<[u32; 3], c_int> {
+    let mut ids = [0u32; 3];
+    let [r, e, s] = &mut ids;
+    // SAFETY: The pointers are valid for writes.
+    let ret = unsafe {
+        syscall3(
+            SYS_GETRESUID,
+            r as *mut u32 as usize,
+            e as *mut u32 as usize,
+            s as *mut u32 as usize,
+        )
+    };
+    result(ret).map(|_| ids)
+}
+
+pub(crate) fn getresgid() -> Result<[u32; 3], c_int> {
+    let mut ids = [0u32; 3];
+    let [r, e, s] = &mut ids;
+    // SAFETY: The pointers are valid for writes.
+    let ret = unsafe {
+        syscall3(
+            SYS_GETRESGID,
+            r as *mut u32 as usize,
+            e as *mut u32 as usize,
+            s as *mut u32 as usize,
+        )
+    };
+    result(ret).map(|_| ids)
+}
+
+#[cfg(feature = "force-env")]
+pub(crate) fn env_var_is(name: &CStr, value: &[u8]) -> bool {
+    use super::{EINTR, O_CLOEXEC, O_RDONLY};
+
+    // Without the C library, the environment can only be read from /proc/self/environ.
+    // This file contains the initial environment as a sequence of nul-terminated
+    // entries.
+    let name = name.to_bytes();
+    let entry_len = name.len() + 1 + value.len();
+    let entry_byte = |i: usize| match i {
+        _ if i < name.len() => name[i],
+        _ if i == name.len() => b'=',
+        _ => value[i - name.len() - 1],
+    };
+    let Ok(fd) = open(c"/proc/self/environ", O_RDONLY | O_CLOEXEC) else {
+        return false;
+    };
+    let mut buf = [0; 256];
+    // The number of bytes of the current entry that match, or None if the current entry
+    // does not match.
+    let mut matched = Some(0);
+    let found = 'outer: loop {
+        let n = match read(fd, &mut buf) {
+            Ok(0) => break false,
+            Ok(n) => n,
+            Err(EINTR) => continue,
+            Err(_) => break false,
+        };
+        for &b in &buf[..n] {
+            if b == 0 {
+                if matched == Some(entry_len) {
+                    break 'outer true;
+                }
+                matched = Some(0);
+            } else {
+                matched = matched.filter(|&m| m < entry_len && entry_byte(m) == b);
+                matched = matched.map(|m| m + 1);
+            }
+        }
+    };
+    close(fd);
+    found
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn getenv(name: &CStr) -> Option<alloc::ffi::CString> {
+    use super::{EINTR, O_CLOEXEC, O_RDONLY};
+
+    // Without the C library, the environment can only be read from /proc/self/environ.
+    let fd = open(c"/proc/self/environ", O_RDONLY | O_CLOEXEC).ok()?;
+    let mut environ = alloc::vec::Vec::new();
+    let mut buf = [0; 256];
+    let res = loop {
+        match read(fd, &mut buf) {
+            Ok(0) => break Some(()),
+            Ok(n) => environ.extend_from_slice(&buf[..n]),
+            Err(EINTR) => {}
+            Err(_) => break None,
+        }
+    };
+    close(fd);
+    res?;
+    let name = name.to_bytes();
+    environ.split(|&b| b == 0).find_map(|entry| {
+        let value = entry.strip_prefix(name)?.strip_prefix(b"=")?;
+        alloc::ffi::CString::new(value).ok()
+    })
+}
@@ -0,0 +1,141 @@
+use {
+    crate::requires_secure_execution,
+    std::{env, ffi::OsString, vec::Vec},
+};
+
+/// The locale that is used if secure execution is required and the environment does not
+/// contain an acceptable locale.
+pub const FALLBACK_LOCALE: &str = "C.UTF-8";
+
+/// A locale category.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum LocaleCategory {
+    /// `LC_CTYPE`
+    Ctype,
+    /// `LC_NUMERIC`
+    Numeric,
+    /// `LC_TIME`
+    Time,
+    /// `LC_COLLATE`
+    Collate,
+    /// `LC_MONETARY`
+    Monetary,
+    /// `LC_MESSAGES`
+    Messages,
+}
+
+/// The locale of a category as determined by [`secure_locale`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SecureLocale {
+    /// The effective locale.
+    pub locale: OsString,
+    /// The value of `LOCPATH`. This is always `None` if secure execution is required.
+    pub locpath: Option<OsString>,
+    /// The value of `NLSPATH`. This is always `None` if secure execution is required.
+    pub nlspath: Option<OsString>,
+    /// The names of the variables that were ignored because secure execution is
+    /// required.
+    pub rejected: Vec<OsString>,
+}
+
+impl LocaleCategory {
+    /// Returns the name of the environment variable of this category.
+    pub const fn env_name(self) -> &'static str {
+        match self {
+            LocaleCategory::Ctype => "LC_CTYPE",
+            LocaleCategory::Numeric => "LC_NUMERIC",
+            LocaleCategory::Time => "LC_TIME",
+            LocaleCategory::Collate => "LC_COLLATE",
+            LocaleCategory::Monetary => "LC_MONETARY",
+            LocaleCategory::Messages => "LC_MESSAGES",
+        }
+    }
+}
+
+/// Determines the locale of a category from the environment.
+///
+/// The locale is taken from the first of `LC_ALL`, the variable of the category, and
+/// `LANG` that is set to a non-empty value. If none of them is set, the locale is `C`.
+///
+/// If [`requires_secure_execution`] returns `true`,
+///
+/// - `LOCPATH` and `NLSPATH` are ignored,
+/// - the locale variables are only accepted if they have the form
+///   `language[_territory][.codeset][@modifier]` or are `C` or `POSIX`, optionally with
+///   a codeset, where all components consist of ASCII letters, digits, `_`, and `-`,
+/// - the locale is [`FALLBACK_LOCALE`] if the variable that would otherwise be used is
+///   not accepted or if none of them is set.
+///
+/// All ignored variables that are set are returned in [`SecureLocale::rejected`].
+pub fn secure_locale(category: LocaleCategory) -> SecureLocale {
+    let names = ["LC_ALL", category.env_name(), "LANG"];
+    let selected = names
+        .iter()
+        .find_map(|name| env::var_os(name).filter(|v| !v.is_empty()));
+    if !requires_secure_execution() {
+        return SecureLocale {
+            locale: selected.unwrap_or_else(|| "C".into()),
+            locpath: env::var_os("LOCPATH"),
+            nlspath: env::var_os("NLSPATH"),
+            rejected: Vec::new(),
+        };
+    }
+    let mut rejected = Vec::new();
+    for name in names {
+        if env::var_os(name).is_some_and(|v| !v.is_empty() && !is_locale_name(v.as_encoded_bytes()))
+        {
+            rejected.push(name.into());
+        }
+    }
+    for name in ["LOCPATH", "NLSPATH"] {
+        if env::var_os(name).is_some() {
+            rejected.push(name.into());
+        }
+    }
+    let locale = match selected {
+        Some(v) if is_locale_name(v.as_encoded_bytes()) => v,
+        _ => FALLBACK_LOCALE.into(),
+    };
+    SecureLocale {
+        locale,
+        locpath: None,
+        nlspath: None,
+        rejected,
+    }
+}
+
+/// Returns whether the value has the form `language[_territory][.codeset][@modifier]`.
+fn is_locale_name(v: &[u8]) -> bool {
+    fn component(v: &[u8], delims: &[u8]) -> (bool, usize) {
+        let len = v.iter().position(|b| delims.contains(b)).unwrap_or(v.len());
+        let valid = (1..=32).contains(&len)
+            && v[..len]
+                .iter()
+                .all(|&b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+        (valid, len)
+    }
+    let lang_len = v.iter().position(|b| b"_.@".contains(b)).unwrap_or(v.len());
+    let lang = &v[..lang_len];
+    let is_posix = lang == b"C" || lang == b"POSIX";
+    let is_language = (2..=3).contains(&lang.len()) && lang.iter().all(u8::is_ascii_lowercase);
+    if !(is_posix || is_language) {
+        return false;
+    }
+    let mut rest = &v[lang_len..];
+    for (prefix, delims, allowed) in [
+        (b'_', &b".@"[..], !is_posix),
+        (b'.', &b"@"[..], true),
+        (b'@', &b""[..], !is_posix),
+    ] {
+        if let Some(tail) = rest.strip_prefix(&[prefix]) {
+            let (valid, len) = component(tail, delims);
+            if !allowed || !valid {
+                return false;
+            }
+            rest = &tail[len..];
+        }
+    }
+    rest.is_empty()
+}
@@ -0,0 +1,336 @@
+//! Reentrant access to the user and group databases.
+
+use {
+    core::{
+        error::Error,
+        ffi::{c_char, c_int, CStr},
+        fmt::{self, Display, Formatter},
+        mem::MaybeUninit,
+        ptr,
+    },
+    std::{
+        ffi::{OsStr, OsString},
+        io,
+        os::unix::ffi::OsStrExt,
+        path::PathBuf,
+        vec::Vec,
+    },
+};
+
+const EINVAL: c_int = 22;
+const ERANGE: c_int = 34;
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))] {
+        #[cfg(any(
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "watchos",
+            target_os = "tvos",
+            target_os = "visionos",
+        ))]
+        type TimeT = core::ffi::c_long;
+        #[cfg(all(any(target_os = "dragonfly", target_os = "freebsd"), target_arch = "x86"))]
+        type TimeT = i32;
+        #[cfg(any(
+            all(any(target_os = "dragonfly", target_os = "freebsd"), not(target_arch = "x86")),
+            target_os = "netbsd",
+            target_os = "openbsd",
+        ))]
+        type TimeT = i64;
+
+        #[repr(C)]
+        struct RawPasswd {
+            pw_name: *mut c_char,
+            _pw_passwd: *mut c_char,
+            pw_uid: u32,
+            _pw_gid: u32,
+            _pw_change: TimeT,
+            _pw_class: *mut c_char,
+            _pw_gecos: *mut c_char,
+            pw_dir: *mut c_char,
+            pw_shell: *mut c_char,
+            // Platform-specific fields that are not used.
+            _reserved: [u64; 8],
+        }
+    } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+        #[repr(C)]
+        struct RawPasswd {
+            pw_name: *mut c_char,
+            _pw_passwd: *mut c_char,
+            pw_uid: u32,
+            _pw_gid: u32,
+            _pw_age: *mut c_char,
+            _pw_comment: *mut c_char,
+            _pw_gecos: *mut c_char,
+            pw_dir: *mut c_char,
+            pw_shell: *mut c_char,
+        }
+    } else if #[cfg(all(target_os = "android", target_pointer_width = "32"))] {
+        #[repr(C)]
+        struct RawPasswd {
+            pw_name: *mut c_char,
+            _pw_passwd: *mut c_char,
+            pw_uid: u32,
+            _pw_gid: u32,
+            pw_dir: *mut c_char,
+            pw_shell: *mut c_char,
+        }
+    } else {
+        #[repr(C)]
+        struct RawPasswd {
+            pw_name: *mut c_char,
+            _pw_passwd: *mut c_char,
+            pw_uid: u32,
+            _pw_gid: u32,
+            _pw_gecos: *mut c_char,
+            pw_dir: *mut c_char,
+            pw_shell: *mut c_char,
+        }
+    }
+}
+
+#[repr(C)]
+struct RawGroup {
+    gr_name: *mut c_char,
+    _gr_passwd: *mut c_char,
+    gr_gid: u32,
+    _gr_mem: *mut *mut c_char,
+}
+
+#[link(name = "c")]
+unsafe extern "C" {
+    pub(crate) safe fn getuid() -> u32;
+    safe fn getgid() -> u32;
+    fn getgroups(size: c_int, list: *mut u32) -> c_int;
+    #[cfg_attr(target_os = "netbsd", link_name = "__getpwuid_r50")]
+    #[cfg_attr(
+        any(target_os = "illumos", target_os = "solaris"),
+        link_name = "__posix_getpwuid_r"
+    )]
+    fn getpwuid_r(
+        uid: u32,
+        pwd: *mut RawPasswd,
+        buf: *mut c_char,
+        buflen: usize,
+        result: *mut *mut RawPasswd,
+    ) -> c_int;
+    #[cfg_attr(target_os = "netbsd", link_name = "__getgrgid_r50")]
+    #[cfg_attr(
+        any(target_os = "illumos", target_os = "solaris"),
+        link_name = "__posix_getgrgid_r"
+    )]
+    fn getgrgid_r(
+        gid: u32,
+        grp: *mut RawGroup,
+        buf: *mut c_char,
+        buflen: usize,
+        result: *mut *mut RawGroup,
+    ) -> c_int;
+}
+
+/// The user that executed the program.
+///
+/// This is created by [`invoking_user`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct InvokingUser {
+    /// The real user ID.
+    pub uid: u32,
+    /// The login name of the user.
+    pub name: OsString,
+    /// The home directory of the user.
+    pub home: PathBuf,
+    /// The login shell of the user.
+    pub shell: PathBuf,
+    /// The real group ID.
+    pub group: Group,
+    /// The supplementary groups of the process.
+    pub groups: Vec<Group>,
+}
+
+/// An entry of the group database.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Group {
+    /// The group ID.
+    pub gid: u32,
+    /// The name of the group.
+    pub name: OsString,
+}
+
+/// An error returned by [`invoking_user`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InvokingUserError {
+    /// The user database does not contain the real user ID.
+    NoSuchUser(u32),
+    /// The group database does not contain a group ID of the process.
+    NoSuchGroup(u32),
+    /// The databases could not be read.
+    Io(io::Error),
+}
+
+/// An entry of the user database.
+pub(crate) struct Passwd {
+    pub(crate) name: OsString,
+    pub(crate) uid: u32,
+    pub(crate) dir: OsString,
+    pub(crate) shell: OsString,
+}
+
+/// Returns the user that executed the program.
+///
+/// The information is taken from the user and group databases using the real user and
+/// group IDs and the supplementary groups of the process. Environment variables such as
+/// `USER`, `LOGNAME`, or `SUDO_USER` are never used. Therefore, the result can be trusted
+/// even if secure execution is required.
+///
+/// The primary group is not contained in [`InvokingUser::groups`].
+pub fn invoking_user() -> Result<InvokingUser, InvokingUserError> {
+    let uid = getuid();
+    let pwd = getpwuid(uid)
+        .map_err(InvokingUserError::Io)?
+        .ok_or(InvokingUserError::NoSuchUser(uid))?;
+    let gid = getgid();
+    let mut groups = Vec::new();
+    for id in supplementary_groups().map_err(InvokingUserError::Io)? {
+        if id != gid && !groups.iter().any(|g: &Group| g.gid == id) {
+            groups.push(group(id)?);
+        }
+    }
+    Ok(InvokingUser {
+        uid: pwd.uid,
+        name: pwd.name,
+        home: pwd.dir.into(),
+        shell: pwd.shell.into(),
+        group: group(gid)?,
+        groups,
+    })
+}
+
+/// Returns the entry of a user or `None` if there is no such user.
+pub(crate) fn getpwuid(uid: u32) -> io::Result<Option<Passwd>> {
+    lookup(
+        // SAFETY: lookup passes valid pointers and the capacity of the buffer.
+        |pwd, buf, len, res| unsafe { getpwuid_r(uid, pwd, buf, len, res) },
+        |pwd: &RawPasswd| Passwd {
+            // SAFETY: The fields are nul-terminated strings or null.
+            name: unsafe { string(pwd.pw_name) },
+            uid: pwd.pw_uid,
+            dir: unsafe { string(pwd.pw_dir) },
+            shell: unsafe { string(pwd.pw_shell) },
+        },
+    )
+}
+
+fn group(gid: u32) -> Result<Group, InvokingUserError> {
+    lookup(
+        // SAFETY: lookup passes valid pointers and the capacity of the buffer.
+        |grp, buf, len, res| unsafe { getgrgid_r(gid, grp, buf, len, res) },
+        |grp: &RawGroup| Group {
+            gid: grp.gr_gid,
+            // SAFETY: The name is a nul-terminated string or null.
+            name: unsafe { string(grp.gr_name) },
+        },
+    )
+    .map_err(InvokingUserError::Io)?
+    .ok_or(InvokingUserError::NoSuchGroup(gid))
+}
+
+fn supplementary_groups() -> io::Result<Vec<u32>> {
+    loop {
+        // SAFETY: A size of 0 only returns the number of groups.
+        let n = unsafe { getgroups(0, ptr::null_mut()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut groups = Vec::<u32>::with_capacity(n as usize);
+        // SAFETY: groups has a capacity of n.
+        let m = unsafe { getgroups(n, groups.as_mut_ptr()) };
+        if m >= 0 {
+            // SAFETY: getgroups initialized m <= n elements.
+            unsafe { groups.set_len(m as usize) };
+            return Ok(groups);
+        }
+        // The groups might have changed between the two calls.
+        if io::Error::last_os_error().raw_os_error() != Some(EINVAL) {
+            return Err(io::Error::last_os_error());
+        }
+    }
+}
+
+/// Calls a reentrant lookup function with growing buffers.
+fn lookup<R, T>(
+    mut f: impl FnMut(*mut R, *mut c_char, usize, *mut *mut R) -> c_int,
+    convert: impl FnOnce(&R) -> T,
+) -> io::Result<Option<T>> {
+    let mut buf = Vec::<c_char>::with_capacity(1024);
+    loop {
+        let mut entry = MaybeUninit::<R>::uninit();
+        let mut result = ptr::null_mut();
+        let res = f(
+            entry.as_mut_ptr(),
+            buf.as_mut_ptr(),
+            buf.capacity(),
+            &mut result,
+        );
+        if res == ERANGE && buf.capacity() < 1 << 20 {
+            buf.reserve(buf.capacity() * 2);
+            continue;
+        }
+        if res != 0 {
+            return Err(io::Error::from_raw_os_error(res));
+        }
+        if result.is_null() {
+            return Ok(None);
+        }
+        // SAFETY: The lookup succeeded and initialized the entry. The strings in the
+        // entry point into buf, which is still alive.
+        return Ok(Some(convert(unsafe { entry.assume_init_ref() })));
+    }
+}
+
+/// # Safety
+///
+/// `s` must be null or a nul-terminated string.
+unsafe fn string(s: *const c_char) -> OsString {
+    if s.is_null() {
+        return OsString::new();
+    }
+    // SAFETY: Guaranteed by the caller.
+    OsStr::from_bytes(unsafe { CStr::from_ptr(s) }.to_bytes()).into()
+}
+
+impl Display for InvokingUserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InvokingUserError::NoSuchUser(uid) => {
+                write!(f, "the user database does not contain user {uid}")
+            }
+            InvokingUserError::NoSuchGroup(gid) => {
+                write!(f, "the group database does not contain group {gid}")
+            }
+            InvokingUserError::Io(e) => write!(f, "could not read the user database: {e}"),
+        }
+    }
+}
+
+impl Error for InvokingUserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InvokingUserError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
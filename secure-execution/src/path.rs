@@ -0,0 +1,94 @@
+#[cfg(unix)]
+use std::{
+    ffi::OsStr,
+    fs,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+use {
+    crate::requires_secure_execution,
+    std::{env, ffi::OsString},
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "android")] {
+        /// The `PATH` that is used if secure execution is required.
+        pub const DEFAULT_TRUSTED_PATH: &str = "/system/bin:/system/xbin:/vendor/bin";
+    } else if #[cfg(any(target_os = "illumos", target_os = "solaris"))] {
+        /// The `PATH` that is used if secure execution is required.
+        pub const DEFAULT_TRUSTED_PATH: &str = "/usr/sbin:/usr/bin";
+    } else if #[cfg(unix)] {
+        /// The `PATH` that is used if secure execution is required.
+        pub const DEFAULT_TRUSTED_PATH: &str = "/usr/sbin:/usr/bin:/sbin:/bin";
+    } else if #[cfg(windows)] {
+        /// The `PATH` that is used if secure execution is required.
+        ///
+        /// These are the directories of the default system `PATH` for Windows installed in
+        /// `C:\Windows`.
+        pub const DEFAULT_TRUSTED_PATH: &str =
+            r"C:\Windows\system32;C:\Windows;C:\Windows\System32\Wbem";
+    } else {
+        /// The `PATH` that is used if secure execution is required.
+        ///
+        /// This crate does not know any trusted directories on this platform, so this path
+        /// is empty.
+        pub const DEFAULT_TRUSTED_PATH: &str = "";
+    }
+}
+
+/// Returns the search path for executables.
+///
+/// If [`requires_secure_execution`] returns `true`, this function returns
+/// [`DEFAULT_TRUSTED_PATH`]. Otherwise, it returns the `PATH` environment variable or
+/// [`DEFAULT_TRUSTED_PATH`] if it is not set.
+pub fn secure_path() -> OsString {
+    if !requires_secure_execution() {
+        if let Some(path) = env::var_os("PATH") {
+            return path;
+        }
+    }
+    DEFAULT_TRUSTED_PATH.into()
+}
+
+/// Searches [`secure_path`] for an executable that can only have been modified by root.
+///
+/// This function returns the canonical path of the first executable regular file called
+/// `name` in an absolute directory of the search path such that the file and all of its
+/// ancestors are owned by root and not writable by group or others. Relative directories
+/// in the search path and names that contain `/` are ignored.
+///
+/// Unlike [`secure_path`], these checks are performed even if secure execution is not
+/// required.
+#[cfg(unix)]
+pub fn find_trusted_executable(name: impl AsRef<OsStr>) -> Option<PathBuf> {
+    let name = name.as_ref();
+    if name.is_empty() || name.as_encoded_bytes().contains(&b'/') {
+        return None;
+    }
+    env::split_paths(&secure_path())
+        .filter(|dir| dir.is_absolute())
+        .filter_map(|dir| dir.join(name).canonicalize().ok())
+        .find(|path| is_trusted_executable(path))
+}
+
+#[cfg(unix)]
+fn is_trusted_executable(path: &Path) -> bool {
+    let Ok(meta) = fs::metadata(path) else {
+        return false;
+    };
+    meta.is_file() && meta.permissions().mode() & 0o111 != 0 && is_root_controlled(path)
+}
+
+/// Returns whether only root can have modified the file at an absolute path or any of
+/// its ancestors.
+///
+/// The path and all of its ancestors must be owned by root, must not be writable by
+/// group or others, and must not be symbolic links.
+#[cfg(unix)]
+pub(crate) fn is_root_controlled(path: &Path) -> bool {
+    let is_trusted = |path: &Path| {
+        fs::symlink_metadata(path)
+            .is_ok_and(|m| m.uid() == 0 && m.mode() & 0o022 == 0 && !m.file_type().is_symlink())
+    };
+    path.is_absolute() && path.ancestors().all(is_trusted)
+}
@@ -0,0 +1,198 @@
+use {
+    crate::{
+        auxv::{self, AT_EXECFN},
+        environ, requires_secure_execution, ScrubPolicy,
+    },
+    core::{
+        error::Error,
+        ffi::{c_char, c_int, c_ulong, CStr},
+        fmt::{self, Display, Formatter},
+        ptr,
+    },
+    std::{env, ffi::CString, io, os::unix::ffi::OsStrExt, vec::Vec},
+};
+
+#[link(name = "c")]
+unsafe extern "C" {
+    fn execve(path: *const c_char, argv: *const *const c_char, envp: *const *const c_char)
+        -> c_int;
+    #[link_name = "getauxval"]
+    safe fn libc_getauxval(ty: c_ulong) -> c_ulong;
+}
+
+/// The path that the program is re-executed from.
+///
+/// This path always refers to the file that was executed, even if the path it was
+/// executed from has since been replaced or removed. Executing it is therefore equivalent
+/// to calling `fexecve` with a file descriptor opened at startup.
+const EXE: &CStr = c"/proc/self/exe";
+
+/// An error returned by [`reexec_with_clean_environment`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReexecError {
+    /// The program has already been re-executed but the environment still contains
+    /// variables that are not allowed by the policy.
+    Loop,
+    /// The program could not be re-executed.
+    Io(io::Error),
+}
+
+/// Re-executes the program with the environment produced by [`scrub_environment`] if
+/// secure execution is required and the environment contains variables that are not
+/// allowed by the policy.
+///
+/// In contrast to [`scrub_environment`], this also undoes anything that the dynamic
+/// loader or constructors have done with the environment before `main`. If
+/// [`requires_secure_execution`] returns `false`, this function returns `Ok(())`. If the
+/// environment is already clean, it sets the variables passed to [`ScrubPolicy::reset`]
+/// and returns `Ok(())`. Otherwise, it executes `/proc/self/exe` with the original
+/// arguments and the sanitized environment and only returns on error.
+///
+/// The environment is clean if every entry is allowed by the policy or belongs to a
+/// variable passed to [`ScrubPolicy::reset`] and has the reset value. Such variables may
+/// also be missing since the dynamic loader removes some of them, for example `TMPDIR`,
+/// in secure execution. This is why they are set again before this function returns.
+///
+/// The program is re-executed at most once. Whether the program has already been
+/// re-executed is determined from the `AT_EXECFN` entry of the auxiliary vector, which
+/// contains the path passed to `execve`, and not from the environment. If the
+/// environment of a re-executed program is still not clean, this function returns
+/// [`ReexecError::Loop`] instead of executing the program again.
+///
+/// This function should be the first statement of `main`.
+///
+/// [`scrub_environment`]: crate::scrub_environment
+///
+/// # Errors
+///
+/// If this function returns an error, the environment has not been sanitized. The
+/// program must treat every error as fatal and exit without using the environment.
+///
+/// # Safety
+///
+/// No other threads must exist.
+pub unsafe fn reexec_with_clean_environment(policy: &ScrubPolicy) -> Result<(), ReexecError> {
+    if !requires_secure_execution() {
+        return Ok(());
+    }
+    // SAFETY: The caller guarantees that no other threads exist.
+    let current: Vec<_> = unsafe { environ::entries() }.collect();
+    let Some(kept) = sanitize(policy, &current) else {
+        for (name, value) in &policy.resets {
+            env::set_var(name, value);
+        }
+        return Ok(());
+    };
+    if is_reexecuted() {
+        return Err(ReexecError::Loop);
+    }
+    let envp = kept
+        .into_iter()
+        .chain(resets(policy))
+        .map(CString::new)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ReexecError::Io(e.into()))?;
+    let argv = env::args_os()
+        .map(|a| CString::new(a.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ReexecError::Io(e.into()))?;
+    let argv = nul_terminated(&argv);
+    let envp = nul_terminated(&envp);
+    // SAFETY: EXE is nul-terminated, and argv and envp are null-terminated arrays of
+    // nul-terminated strings.
+    unsafe {
+        execve(EXE.as_ptr(), argv.as_ptr(), envp.as_ptr());
+    }
+    Err(ReexecError::Io(io::Error::last_os_error()))
+}
+
+/// Returns whether the program has been executed by [`reexec_with_clean_environment`].
+///
+/// Unlike a variable in the environment, the path passed to `execve` cannot be chosen
+/// freely by the caller: `/proc/self/exe` only refers to this program if the process
+/// executing it already runs this program.
+fn is_reexecuted() -> bool {
+    // With the raw-syscalls feature, auxv::getauxval reads /proc/self/auxv, which
+    // processes that are not dumpable cannot read. The C library always has access.
+    let execfn = auxv::getauxval(AT_EXECFN).unwrap_or_else(|| libc_getauxval(AT_EXECFN));
+    // SAFETY: The kernel places a nul-terminated string at this address that is never
+    // deallocated.
+    execfn != 0 && unsafe { CStr::from_ptr(execfn as *const c_char) } == EXE
+}
+
+/// Returns the entries that [`scrub_environment`](crate::scrub_environment) would keep
+/// in the environment or `None` if the environment is already clean.
+fn sanitize(policy: &ScrubPolicy, entries: &[&[u8]]) -> Option<Vec<Vec<u8>>> {
+    let mut names = Vec::<&[u8]>::new();
+    let mut kept = Vec::new();
+    let mut clean = true;
+    for &entry in entries {
+        let Some(pos @ 1..) = entry.iter().position(|&b| b == b'=') else {
+            clean = false;
+            continue;
+        };
+        let (name, value) = (&entry[..pos], &entry[pos + 1..]);
+        let first = !names.contains(&name);
+        names.push(name);
+        if first && policy.keeps(name, value) {
+            kept.push(entry.to_vec());
+        } else if !first || reset_value(policy, name) != Some(value) {
+            clean = false;
+        }
+    }
+    (!clean).then_some(kept)
+}
+
+/// Returns the value that the policy resets a variable to.
+fn reset_value<'a>(policy: &'a ScrubPolicy, name: &[u8]) -> Option<&'a [u8]> {
+    let (_, value) = policy
+        .resets
+        .iter()
+        .rev()
+        .find(|(n, _)| n.as_bytes() == name)?;
+    Some(value.as_bytes())
+}
+
+/// Returns the entries for the variables passed to [`ScrubPolicy::reset`].
+fn resets(policy: &ScrubPolicy) -> impl Iterator<Item = Vec<u8>> + '_ {
+    policy
+        .resets
+        .iter()
+        .enumerate()
+        .filter(|(i, (name, _))| !policy.resets[i + 1..].iter().any(|(n, _)| n == name))
+        .map(|(_, (name, value))| {
+            let mut entry = name.as_bytes().to_vec();
+            entry.push(b'=');
+            entry.extend_from_slice(value.as_bytes());
+            entry
+        })
+}
+
+fn nul_terminated(strings: &[CString]) -> Vec<*const c_char> {
+    strings
+        .iter()
+        .map(|s| s.as_ptr())
+        .chain([ptr::null()])
+        .collect()
+}
+
+impl Display for ReexecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReexecError::Loop => f.write_str(
+                "the environment is not clean even though the program has been re-executed",
+            ),
+            ReexecError::Io(e) => write!(f, "could not re-execute the program: {e}"),
+        }
+    }
+}
+
+impl Error for ReexecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReexecError::Loop => None,
+            ReexecError::Io(e) => Some(e),
+        }
+    }
+}
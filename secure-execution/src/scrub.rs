@@ -0,0 +1,161 @@
+use {
+    crate::{environ::environ, requires_secure_execution, SecureEnv, Validator},
+    core::{ffi::CStr, ptr},
+    std::{
+        env,
+        ffi::{OsStr, OsString},
+        os::unix::ffi::OsStrExt,
+        vec::Vec,
+    },
+};
+
+/// The variables that [`scrub_environment`] keeps in secure execution.
+///
+/// ```
+/// use secure_execution::{ScrubPolicy, Validator};
+///
+/// let policy = ScrubPolicy::new()
+///     .allow("TERM", Validator::max_len(64).and(Validator::no_slash_or_percent()))
+///     .reset("PATH", "/usr/bin:/bin");
+/// ```
+///
+/// The allowed variables can also be taken from a [`SecureEnv`]:
+///
+/// ```
+/// use secure_execution::{ScrubPolicy, SecureEnv, Validator};
+///
+/// let env = SecureEnv::new().allow("TERM", Validator::no_slash_or_percent());
+/// let policy = ScrubPolicy::from(env).reset("PATH", "/usr/bin:/bin");
+/// ```
+#[derive(Debug, Default)]
+pub struct ScrubPolicy {
+    allowed: SecureEnv,
+    pub(crate) resets: Vec<(OsString, OsString)>,
+}
+
+/// The changes made by [`scrub_environment`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ScrubReport {
+    /// Whether the environment was scrubbed. This is `false` if secure execution is not
+    /// required.
+    pub scrubbed: bool,
+    /// The names of the removed variables in the order in which they appeared in the
+    /// environment. A name appears multiple times if the variable was set multiple times.
+    /// For entries that do not contain `=`, this contains the whole entry.
+    pub removed: Vec<OsString>,
+    /// The names of the variables that were reset to the values in the policy.
+    pub reset: Vec<OsString>,
+}
+
+impl ScrubPolicy {
+    /// Creates a policy that removes all variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps a variable if its value is accepted by the validator.
+    ///
+    /// See [`SecureEnv::allow`] for details.
+    pub fn allow(mut self, name: impl Into<OsString>, validator: Validator) -> Self {
+        self.allowed = self.allowed.allow(name, validator);
+        self
+    }
+
+    /// Sets a variable to a fixed value, regardless of its previous value.
+    ///
+    /// If the same variable is reset multiple times, the last value is used.
+    pub fn reset(mut self, name: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.resets.push((name.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn keeps(&self, name: &[u8], value: &[u8]) -> bool {
+        let name = OsStr::from_bytes(name);
+        if self.resets.iter().any(|(n, _)| n == name) {
+            return false;
+        }
+        self.allowed
+            .validator(name)
+            .is_some_and(|v| v.accepts(OsStr::from_bytes(value)))
+    }
+}
+
+impl From<SecureEnv> for ScrubPolicy {
+    /// Creates a policy that keeps the variables allowed by `env`.
+    fn from(env: SecureEnv) -> Self {
+        Self {
+            allowed: env,
+            resets: Vec::new(),
+        }
+    }
+}
+
+/// Removes all variables from the environment that are not allowed by the policy if
+/// secure execution is required.
+///
+/// If [`requires_secure_execution`] returns `false`, this function does nothing.
+/// Otherwise, it operates directly on the environment block of the C library and removes
+///
+/// - all entries whose variable is not allowed by the policy or whose value is rejected
+///   by its validator,
+/// - all but the first entry of each allowed variable,
+/// - all entries that do not contain `=` or have an empty name.
+///
+/// Afterwards, the variables passed to [`ScrubPolicy::reset`] are set.
+///
+/// This function should be called at the very start of `main`. It cannot undo anything
+/// that the dynamic loader or constructors have done with the environment before.
+///
+/// # Safety
+///
+/// No other threads must exist and no references into the environment, for example
+/// pointers returned by `getenv`, must be used after this function returns.
+pub unsafe fn scrub_environment(policy: &ScrubPolicy) -> ScrubReport {
+    let mut report = ScrubReport::default();
+    if !requires_secure_execution() {
+        return report;
+    }
+    report.scrubbed = true;
+    let envp = environ();
+    if !envp.is_null() {
+        let mut kept = Vec::<&[u8]>::new();
+        let mut read = envp;
+        let mut write = envp;
+        // SAFETY: The environment block is a null-terminated array of nul-terminated
+        // strings. Since no other threads exist, we have exclusive access to it.
+        // Entries are only moved towards the front, so each write position has already
+        // been read.
+        unsafe {
+            while !(*read).is_null() {
+                let entry = *read;
+                read = read.add(1);
+                let bytes = CStr::from_ptr(entry).to_bytes();
+                let removed = match bytes.iter().position(|&b| b == b'=') {
+                    Some(pos @ 1..) => {
+                        let (name, value) = (&bytes[..pos], &bytes[pos + 1..]);
+                        if !kept.contains(&name) && policy.keeps(name, value) {
+                            kept.push(name);
+                            *write = entry;
+                            write = write.add(1);
+                            continue;
+                        }
+                        name
+                    }
+                    _ => bytes,
+                };
+                if !policy.resets.iter().any(|(n, _)| n.as_bytes() == removed) {
+                    report.removed.push(OsStr::from_bytes(removed).into());
+                }
+            }
+            *write = ptr::null_mut();
+        }
+    }
+    for (name, value) in &policy.resets {
+        env::set_var(name, value);
+        if !report.reset.contains(name) {
+            report.reset.push(name.clone());
+        }
+    }
+    report
+}
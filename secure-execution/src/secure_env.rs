@@ -0,0 +1,168 @@
+use {
+    crate::requires_secure_execution,
+    core::{
+        error::Error,
+        fmt::{self, Debug, Display, Formatter},
+    },
+    std::{
+        boxed::Box,
+        env,
+        ffi::{OsStr, OsString},
+        vec::Vec,
+    },
+};
+
+/// A reader for environment variables that only allows selected variables if secure
+/// execution is required.
+///
+/// If [`requires_secure_execution`] returns `false`, [`SecureEnv::var_os`] behaves like
+/// [`std::env::var_os`]. Otherwise, only variables that have been allowed with
+/// [`SecureEnv::allow`] can be read, and only if their value is accepted by the
+/// associated [`Validator`].
+///
+/// ```
+/// use secure_execution::{SecureEnv, Validator};
+///
+/// let env = SecureEnv::new()
+///     .allow("TERM", Validator::max_len(64).and(Validator::no_slash_or_percent()))
+///     .allow("LANG", Validator::charset(|b| b.is_ascii_alphanumeric() || b"._-@".contains(&b)));
+/// let term = env.var_os("TERM");
+/// ```
+#[derive(Debug, Default)]
+pub struct SecureEnv {
+    rules: Vec<(OsString, Validator)>,
+}
+
+/// A predicate that decides whether the value of an environment variable may be used if
+/// secure execution is required.
+///
+/// See the documentation of [`SecureEnv`] for details.
+pub struct Validator {
+    f: Box<dyn Fn(&OsStr) -> bool + Send + Sync>,
+}
+
+/// The reason why [`SecureEnv::var_os`] did not return the value of a variable.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Denied {
+    /// Secure execution is required and the variable has not been allowed.
+    NotAllowed,
+    /// Secure execution is required and the value of the variable was rejected by its
+    /// validator.
+    Invalid,
+}
+
+impl SecureEnv {
+    /// Creates a reader that does not allow any variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows a variable if its value is accepted by the validator.
+    ///
+    /// If the same variable is allowed multiple times, the first validator is used.
+    pub fn allow(mut self, name: impl Into<OsString>, validator: Validator) -> Self {
+        self.rules.push((name.into(), validator));
+        self
+    }
+
+    /// Returns the value of an environment variable.
+    ///
+    /// This function returns `Ok(None)` if the variable is not set and
+    /// `Err(Denied::NotAllowed)` if secure execution is required and the variable has not
+    /// been allowed, regardless of whether it is set.
+    pub fn var_os(&self, name: impl AsRef<OsStr>) -> Result<Option<OsString>, Denied> {
+        let name = name.as_ref();
+        if !requires_secure_execution() {
+            return Ok(env::var_os(name));
+        }
+        let Some(validator) = self.validator(name) else {
+            return Err(Denied::NotAllowed);
+        };
+        match env::var_os(name) {
+            Some(value) if !validator.accepts(&value) => Err(Denied::Invalid),
+            value => Ok(value),
+        }
+    }
+
+    /// Returns the validator of an allowed variable.
+    pub(crate) fn validator(&self, name: &OsStr) -> Option<&Validator> {
+        self.rules.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Returns the allowed variables and their validators without duplicates.
+    pub(crate) fn rules(&self) -> impl Iterator<Item = (&OsStr, &Validator)> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|&(i, (name, _))| !self.rules[..i].iter().any(|(n, _)| n == name))
+            .map(|(_, (name, validator))| (&**name, validator))
+    }
+}
+
+impl Validator {
+    /// Creates a validator from a function.
+    pub fn new(f: impl Fn(&OsStr) -> bool + Send + Sync + 'static) -> Self {
+        Self { f: Box::new(f) }
+    }
+
+    /// Creates a validator that accepts all values.
+    pub fn any() -> Self {
+        Self::new(|_| true)
+    }
+
+    /// Creates a validator that accepts values of at most `len` bytes.
+    pub fn max_len(len: usize) -> Self {
+        Self::new(move |v| v.len() <= len)
+    }
+
+    /// Creates a validator that accepts values whose bytes are all accepted by `f`.
+    pub fn charset(f: fn(u8) -> bool) -> Self {
+        Self::new(move |v| v.as_encoded_bytes().iter().all(|&b| f(b)))
+    }
+
+    /// Creates a validator that accepts values that contain neither `/` nor `%`.
+    ///
+    /// This is the check that sudo applies to variables such as `TERM` and `LANG`.
+    pub fn no_slash_or_percent() -> Self {
+        Self::charset(|b| b != b'/' && b != b'%')
+    }
+
+    /// Creates a validator that accepts absolute paths of files that exist and can only
+    /// have been modified by root.
+    ///
+    /// The file and all of its ancestors must be owned by root, must not be writable by
+    /// group or others, and must not be symbolic links. This ensures that other users
+    /// can neither modify the file nor replace it after validation.
+    #[cfg(unix)]
+    pub fn root_owned_path() -> Self {
+        Self::new(|v| crate::path::is_root_controlled(std::path::Path::new(v)))
+    }
+
+    /// Creates a validator that accepts values that are accepted by both validators.
+    pub fn and(self, other: Self) -> Self {
+        Self::new(move |v| (self.f)(v) && (other.f)(v))
+    }
+
+    pub(crate) fn accepts(&self, value: &OsStr) -> bool {
+        (self.f)(value)
+    }
+}
+
+impl Debug for Validator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Validator").finish_non_exhaustive()
+    }
+}
+
+impl Display for Denied {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Denied::NotAllowed => "the variable is not allowed in secure execution",
+            Denied::Invalid => "the value of the variable is not allowed in secure execution",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl Error for Denied {}
@@ -0,0 +1,115 @@
+use {
+    crate::requires_secure_execution,
+    core::{
+        ffi::c_int,
+        hash::{BuildHasher, Hasher},
+        sync::atomic::{AtomicU64, Ordering::Relaxed},
+    },
+    std::{
+        collections::hash_map::RandomState,
+        env, format,
+        fs::{self, File, OpenOptions},
+        io,
+        os::unix::fs::{MetadataExt, OpenOptionsExt},
+        path::PathBuf,
+        process,
+        time::SystemTime,
+    },
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "watchos",
+        target_os = "tvos",
+        target_os = "visionos",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))] {
+        const O_NOFOLLOW: c_int = 0x100;
+    } else if #[cfg(any(
+        target_os = "illumos",
+        target_os = "solaris",
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    ))] {
+        const O_NOFOLLOW: c_int = 0x20000;
+    } else if #[cfg(any(
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "m68k",
+    ))] {
+        const O_NOFOLLOW: c_int = 0o100000;
+    } else {
+        const O_NOFOLLOW: c_int = 0o400000;
+    }
+}
+
+/// The temporary directory that is used if secure execution is required.
+const TRUSTED_TEMP_DIR: &str = "/tmp";
+
+const S_ISVTX: u32 = 0o1000;
+
+/// Returns the directory for temporary files.
+///
+/// If [`requires_secure_execution`] returns `false`, this function returns
+/// [`std::env::temp_dir`], which uses `TMPDIR` if it is set.
+///
+/// Otherwise, `TMPDIR` is ignored and this function returns `/tmp` after verifying that
+/// it is a directory that is owned by root and has the sticky bit set. This ensures that
+/// other users cannot remove or replace files created in the directory.
+pub fn secure_temp_dir() -> io::Result<PathBuf> {
+    if !requires_secure_execution() {
+        return Ok(env::temp_dir());
+    }
+    let meta = fs::metadata(TRUSTED_TEMP_DIR)?;
+    if !meta.is_dir() || meta.uid() != 0 || meta.mode() & S_ISVTX == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the temporary directory is not a root-owned directory with the sticky bit",
+        ));
+    }
+    Ok(TRUSTED_TEMP_DIR.into())
+}
+
+/// Creates a new file in [`secure_temp_dir`].
+///
+/// The file is created with `O_CREAT | O_EXCL | O_NOFOLLOW` and mode `0600` under a
+/// name that cannot be predicted by other users. The file is not removed automatically.
+///
+/// This function returns the opened file and its path.
+pub fn secure_tempfile() -> io::Result<(File, PathBuf)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let dir = secure_temp_dir()?;
+    let state = RandomState::new();
+    loop {
+        // The keys of RandomState are chosen randomly by the standard library and are
+        // unknown to other processes.
+        let mut hasher = state.build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Relaxed));
+        hasher.write_u32(process::id());
+        if let Ok(time) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            hasher.write_u128(time.as_nanos());
+        }
+        let path = dir.join(format!(".tmp{:016x}", hasher.finish()));
+        let res = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .custom_flags(O_NOFOLLOW)
+            .open(&path);
+        match res {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
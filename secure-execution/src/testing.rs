@@ -0,0 +1,144 @@
+//! Overrides for testing code that depends on secure execution.
+//!
+//! This module is only available if the `testing` feature is enabled. It allows tests to
+//! force the return value of [`requires_secure_execution`] without building a
+//! set-user-ID binary.
+//!
+//! ```
+//! use secure_execution::{requires_secure_execution, testing};
+//!
+//! let _guard = testing::override_thread(true);
+//! assert!(requires_secure_execution());
+//! ```
+//!
+//! The override only affects functions that report whether secure execution is required.
+//! In particular, [`backend`] continues to report the real mechanism.
+//!
+//! This feature must not be enabled in release builds of programs that actually require
+//! secure execution. If `debug_assertions` are disabled and the real detection reports
+//! that secure execution is required, the first call of [`requires_secure_execution`]
+//! panics. This does not apply if the detection only guesses, that is, if [`backend`]
+//! returns [`Backend::AssumedTrue`].
+//!
+//! [`requires_secure_execution`]: crate::requires_secure_execution
+//! [`backend`]: crate::backend
+//! [`Backend::AssumedTrue`]: crate::Backend::AssumedTrue
+
+use {
+    crate::{backend, Backend},
+    core::{
+        cell::Cell,
+        marker::PhantomData,
+        sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    },
+    std::thread_local,
+};
+
+const NONE: usize = 0;
+const FALSE: usize = 1;
+const TRUE: usize = 2;
+
+static GLOBAL: AtomicUsize = AtomicUsize::new(NONE);
+
+thread_local! {
+    static THREAD: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// A guard that restores the previous override when it is dropped.
+///
+/// This type is created by [`override_global`] and [`override_thread`].
+#[must_use = "the override is removed when the guard is dropped"]
+#[derive(Debug)]
+pub struct OverrideGuard {
+    scope: Scope,
+    prev: Option<bool>,
+    // The thread-local override must be restored on the same thread.
+    _not_send: PhantomData<*const ()>,
+}
+
+#[derive(Debug)]
+enum Scope {
+    Global,
+    Thread,
+}
+
+/// Forces the return value of [`requires_secure_execution`] in all threads.
+///
+/// The override is active until the returned guard is dropped. Thread-local overrides
+/// created with [`override_thread`] take precedence.
+///
+/// Since tests usually run in parallel, prefer [`override_thread`] unless the code
+/// under test spawns threads.
+///
+/// [`requires_secure_execution`]: crate::requires_secure_execution
+pub fn override_global(value: bool) -> OverrideGuard {
+    let prev = GLOBAL.swap(encode(Some(value)), Relaxed);
+    OverrideGuard {
+        scope: Scope::Global,
+        prev: decode(prev),
+        _not_send: PhantomData,
+    }
+}
+
+/// Forces the return value of [`requires_secure_execution`] in the current thread.
+///
+/// The override is active until the returned guard is dropped.
+///
+/// [`requires_secure_execution`]: crate::requires_secure_execution
+pub fn override_thread(value: bool) -> OverrideGuard {
+    let prev = THREAD.with(|t| t.replace(Some(value)));
+    OverrideGuard {
+        scope: Scope::Thread,
+        prev,
+        _not_send: PhantomData,
+    }
+}
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        match self.scope {
+            Scope::Global => GLOBAL.store(encode(self.prev), Relaxed),
+            Scope::Thread => THREAD.with(|t| t.set(self.prev)),
+        }
+    }
+}
+
+/// Returns the active override, if any.
+pub(crate) fn get() -> Option<bool> {
+    // The thread-local might already have been destroyed if this is called from the
+    // destructor of another thread-local.
+    match THREAD.try_with(|t| t.get()) {
+        Ok(Some(value)) => Some(value),
+        _ => decode(GLOBAL.load(Relaxed)),
+    }
+}
+
+/// Panics if the real detection reports that secure execution is required in a release
+/// build.
+///
+/// On platforms where the detection always assumes that secure execution is required,
+/// this would panic in every program, so the check is skipped.
+pub(crate) fn check_release(required: bool) {
+    if cfg!(not(debug_assertions)) && required && backend() != Backend::AssumedTrue {
+        panic!(
+            "the `testing` feature of secure-execution must not be enabled in release builds \
+             of programs that require secure execution",
+        );
+    }
+}
+
+fn encode(value: Option<bool>) -> usize {
+    match value {
+        None => NONE,
+        Some(false) => FALSE,
+        Some(true) => TRUE,
+    }
+}
+
+fn decode(value: usize) -> Option<bool> {
+    match value {
+        FALSE => Some(false),
+        TRUE => Some(true),
+        _ => None,
+    }
+}
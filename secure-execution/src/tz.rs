@@ -0,0 +1,149 @@
+use {
+    crate::requires_secure_execution,
+    std::{
+        env,
+        ffi::{OsStr, OsString},
+        os::unix::ffi::OsStrExt,
+        path::{Component, Path, PathBuf},
+        vec::Vec,
+    },
+};
+
+cfg_if::cfg_if! {
+    if #[cfg(target_os = "android")] {
+        /// The directory containing the time zone database that is used if secure
+        /// execution is required.
+        pub const DEFAULT_TZDIR: &str = "/system/usr/share/zoneinfo";
+    } else {
+        /// The directory containing the time zone database that is used if secure
+        /// execution is required.
+        pub const DEFAULT_TZDIR: &str = "/usr/share/zoneinfo";
+    }
+}
+
+/// The time zone file that is used if `TZ` is not set.
+pub const DEFAULT_TZFILE: &str = "/etc/localtime";
+
+/// A time zone as determined by [`secure_timezone`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TimeZone {
+    /// A file in the time zone database format.
+    File(PathBuf),
+    /// A time zone described by a POSIX `TZ` string such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+    Posix(OsString),
+}
+
+/// The time zone as determined by [`secure_timezone`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SecureTimeZone {
+    /// The effective time zone.
+    pub zone: TimeZone,
+    /// The names of the variables that were ignored because secure execution is
+    /// required.
+    pub rejected: Vec<OsString>,
+}
+
+/// Determines the time zone from the environment.
+///
+/// This function follows the rules of glibc:
+///
+/// - If `TZ` is not set, the time zone is [`DEFAULT_TZFILE`]. If it is empty, the time
+///   zone is UTC.
+/// - A leading `:` is removed from `TZ`.
+/// - If `TZ` is an absolute path, it is used as a time zone file.
+/// - Otherwise, if `TZ` names a file in the time zone database, that file is used. The
+///   database is located in `TZDIR` or [`DEFAULT_TZDIR`] if it is not set.
+/// - Otherwise, `TZ` is used as a POSIX `TZ` string if it is one and UTC otherwise.
+///
+/// If [`requires_secure_execution`] returns `true`, `TZDIR` is ignored and `TZ` is
+/// ignored if it is an absolute path other than [`DEFAULT_TZFILE`] that is not in
+/// [`DEFAULT_TZDIR`] or if it contains a `..` component. In this case, the time zone is
+/// [`DEFAULT_TZFILE`]. All ignored variables that are set are returned in
+/// [`SecureTimeZone::rejected`].
+pub fn secure_timezone() -> SecureTimeZone {
+    let secure = requires_secure_execution();
+    let mut rejected = Vec::new();
+    let mut tzdir = PathBuf::from(DEFAULT_TZDIR);
+    if let Some(dir) = env::var_os("TZDIR") {
+        if secure {
+            rejected.push("TZDIR".into());
+        } else if !dir.is_empty() {
+            tzdir = dir.into();
+        }
+    }
+    let Some(tz) = env::var_os("TZ") else {
+        return SecureTimeZone {
+            zone: TimeZone::File(DEFAULT_TZFILE.into()),
+            rejected,
+        };
+    };
+    let tz = tz.as_bytes();
+    let name = Path::new(OsStr::from_bytes(tz.strip_prefix(b":").unwrap_or(tz)));
+    if secure && !is_trusted_name(name) {
+        rejected.push("TZ".into());
+        return SecureTimeZone {
+            zone: TimeZone::File(DEFAULT_TZFILE.into()),
+            rejected,
+        };
+    }
+    let zone = if name.as_os_str().is_empty() {
+        TimeZone::Posix("UTC0".into())
+    } else if name.is_absolute() {
+        TimeZone::File(name.into())
+    } else if tzdir.join(name).is_file() {
+        TimeZone::File(tzdir.join(name))
+    } else if is_posix_tz(name.as_os_str().as_bytes()) {
+        TimeZone::Posix(name.into())
+    } else {
+        TimeZone::Posix("UTC0".into())
+    };
+    SecureTimeZone { zone, rejected }
+}
+
+/// Returns whether `TZ` can be used in secure execution.
+fn is_trusted_name(name: &Path) -> bool {
+    if name.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    !name.is_absolute() || name == Path::new(DEFAULT_TZFILE) || name.starts_with(DEFAULT_TZDIR)
+}
+
+/// Returns whether the value plausibly is a POSIX `TZ` string.
+///
+/// This checks the standard time zone name and the offset that must follow it. The
+/// remainder must consist of characters that can appear in such strings.
+fn is_posix_tz(v: &[u8]) -> bool {
+    let rest = match v.strip_prefix(b"<") {
+        Some(quoted) => {
+            let Some(end) = quoted.iter().position(|&b| b == b'>') else {
+                return false;
+            };
+            let name = &quoted[..end];
+            if name.len() < 3
+                || !name
+                    .iter()
+                    .all(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-')
+            {
+                return false;
+            }
+            &quoted[end + 1..]
+        }
+        None => {
+            let len = v.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+            if len < 3 {
+                return false;
+            }
+            &v[len..]
+        }
+    };
+    let offset = rest
+        .strip_prefix(b"+")
+        .or(rest.strip_prefix(b"-"))
+        .unwrap_or(rest);
+    offset.first().is_some_and(u8::is_ascii_digit)
+        && rest
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b"<>+-:,./".contains(&b))
+}
@@ -0,0 +1,170 @@
+//! Catalogues of environment variables that are unsafe in secure execution.
+//!
+//! When a program requires secure execution, the dynamic loaders of the common C
+//! libraries remove or ignore a list of environment variables that would otherwise allow
+//! the invoking user to influence the program, for example `LD_PRELOAD`. These rules are
+//! only applied to the process itself. They are neither applied to child processes nor
+//! to code that reads the environment without going through the C library.
+//!
+//! This module contains these lists so that they can be applied to such cases as well.
+//!
+//! The catalogues are updated when the upstream lists change. Such updates are not
+//! considered breaking changes. [`VERSION`] is incremented whenever a catalogue changes.
+
+/// The version of the catalogues in this module.
+pub const VERSION: u32 = 1;
+
+/// A list of environment variables that are unsafe in secure execution.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct Catalogue {
+    /// The C library whose rules this catalogue describes.
+    pub flavor: LibcFlavor,
+    /// The version of the C library that the catalogue was taken from.
+    pub upstream_version: &'static str,
+    /// Names of unsafe variables.
+    pub names: &'static [&'static str],
+    /// Prefixes of names of unsafe variables.
+    pub prefixes: &'static [&'static str],
+}
+
+/// A C library.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum LibcFlavor {
+    /// The GNU C library.
+    Glibc,
+    /// The musl C library.
+    Musl,
+    /// The Android C library.
+    Bionic,
+}
+
+/// The variables that glibc removes from the environment in secure execution.
+///
+/// This is the `UNSECURE_ENVVARS` list from `unsecvars.h`. Additionally, all variables
+/// starting with `MALLOC_` are considered unsafe since their set has changed between
+/// versions.
+pub const GLIBC: Catalogue = Catalogue {
+    flavor: LibcFlavor::Glibc,
+    upstream_version: "2.40",
+    names: &[
+        "GCONV_PATH",
+        "GETCONF_DIR",
+        "GLIBC_TUNABLES",
+        "HOSTALIASES",
+        "LD_AUDIT",
+        "LD_BIND_NOT",
+        "LD_BIND_NOW",
+        "LD_DEBUG",
+        "LD_DEBUG_OUTPUT",
+        "LD_DYNAMIC_WEAK",
+        "LD_HWCAP_MASK",
+        "LD_LIBRARY_PATH",
+        "LD_ORIGIN_PATH",
+        "LD_PRELOAD",
+        "LD_PROFILE",
+        "LD_SHOW_AUXV",
+        "LD_USE_LOAD_BIAS",
+        "LD_VERBOSE",
+        "LD_WARN",
+        "LOCALDOMAIN",
+        "LOCPATH",
+        "NIS_PATH",
+        "NLSPATH",
+        "RESOLV_HOST_CONF",
+        "RES_OPTIONS",
+        "TMPDIR",
+        "TZDIR",
+    ],
+    prefixes: &["MALLOC_"],
+};
+
+/// The variables that musl ignores in secure execution.
+///
+/// musl additionally ignores `TZ` values that refer to files outside of the system
+/// time zone directories. Since such values are not unsafe in general, `TZ` is not part
+/// of this catalogue.
+pub const MUSL: Catalogue = Catalogue {
+    flavor: LibcFlavor::Musl,
+    upstream_version: "1.2.5",
+    names: &["LD_LIBRARY_PATH", "LD_PRELOAD", "MUSL_LOCPATH"],
+    prefixes: &[],
+};
+
+/// The variables that the Android dynamic linker removes from the environment in secure
+/// execution.
+///
+/// This is the `unsafe_variable_names` list from `linker_environ.cpp`.
+pub const BIONIC: Catalogue = Catalogue {
+    flavor: LibcFlavor::Bionic,
+    upstream_version: "android-15",
+    names: &[
+        "ANDROID_DNS_MODE",
+        "GCONV_PATH",
+        "GETCONF_DIR",
+        "HOSTALIASES",
+        "JE_MALLOC_CONF",
+        "LD_AOUT_LIBRARY_PATH",
+        "LD_AOUT_PRELOAD",
+        "LD_AUDIT",
+        "LD_CONFIG_FILE",
+        "LD_DEBUG",
+        "LD_DEBUG_OUTPUT",
+        "LD_DYNAMIC_WEAK",
+        "LD_LIBRARY_PATH",
+        "LD_ORIGIN_PATH",
+        "LD_PRELOAD",
+        "LD_PROFILE",
+        "LD_SHOW_AUXV",
+        "LD_USE_LOAD_BIAS",
+        "LIBC_DEBUG_MALLOC_OPTIONS",
+        "LIBC_HOOKS_ENABLE",
+        "LOCALDOMAIN",
+        "LOCPATH",
+        "MALLOC_CHECK_",
+        "MALLOC_CONF",
+        "MALLOC_TRACE",
+        "NIS_PATH",
+        "NLSPATH",
+        "RESOLV_HOST_CONF",
+        "RES_OPTIONS",
+        "SCUDO_OPTIONS",
+        "TMPDIR",
+        "TZDIR",
+    ],
+    prefixes: &[],
+};
+
+/// All catalogues in this module.
+pub const ALL: &[Catalogue] = &[GLIBC, MUSL, BIONIC];
+
+impl LibcFlavor {
+    /// Returns the catalogue of this C library.
+    pub const fn catalogue(self) -> &'static Catalogue {
+        match self {
+            LibcFlavor::Glibc => &GLIBC,
+            LibcFlavor::Musl => &MUSL,
+            LibcFlavor::Bionic => &BIONIC,
+        }
+    }
+}
+
+impl Catalogue {
+    /// Returns whether the variable is part of this catalogue.
+    pub fn contains(&self, name: impl AsRef<[u8]>) -> bool {
+        let name = name.as_ref();
+        self.names.iter().any(|n| n.as_bytes() == name)
+            || self.prefixes.iter().any(|p| name.starts_with(p.as_bytes()))
+    }
+}
+
+/// Returns whether the variable is unsafe in secure execution.
+///
+/// This function returns `true` if the variable is part of any catalogue in this module.
+/// Since child processes might use a different C library than the current process, this
+/// is the appropriate check in most cases.
+pub fn is_unsafe_env_var(name: impl AsRef<[u8]>) -> bool {
+    let name = name.as_ref();
+    ALL.iter().any(|c| c.contains(name))
+}
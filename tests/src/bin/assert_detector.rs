@@ -0,0 +1,10 @@
+use secure_execution::{
+    backend, requires_secure_execution, set_detector, Backend, SetDetectorError,
+};
+
+fn main() {
+    assert_eq!(set_detector(|| true), Ok(()));
+    assert_eq!(set_detector(|| false), Err(SetDetectorError::AlreadySet));
+    assert!(requires_secure_execution());
+    assert_eq!(backend(), Backend::Custom);
+}
@@ -1,6 +1,126 @@
-use secure_execution::requires_secure_execution;
+use secure_execution::{requires_secure_execution, secure_execution_reason, SecureExecutionReason};
 
 fn main() {
     assert!(!requires_secure_execution());
     assert!(!requires_secure_execution());
+    assert!(!secure_execution::requires_secure_execution_uncached());
+    assert!(!secure_execution::refresh_secure_execution());
+    assert!(!requires_secure_execution());
+    assert_eq!(
+        secure_execution_reason(),
+        SecureExecutionReason::NotRequired
+    );
+    assert!(!secure_execution::requires_secure_execution_with(
+        secure_execution::Semantics::Tainted
+    ));
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(
+        secure_execution::auxv::Auxv::snapshot().secure(),
+        Some(false)
+    );
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(
+        secure_execution::backend(),
+        secure_execution::Backend::Getauxval
+    );
+    assert_eq!(
+        secure_execution::secure_var_os("PATH"),
+        std::env::var_os("PATH")
+    );
+    assert_eq!(
+        secure_execution::secure_vars_os().count(),
+        std::env::vars_os().count()
+    );
+    let env = secure_execution::SecureEnv::new();
+    assert_eq!(env.var_os("PATH"), Ok(std::env::var_os("PATH")));
+    assert!(secure_execution::is_unsafe_env_var("LD_PRELOAD"));
+    assert!(secure_execution::is_unsafe_env_var("MALLOC_ARENA_MAX"));
+    assert!(!secure_execution::is_unsafe_env_var("PATH"));
+    {
+        use secure_execution::SecureCommandExt;
+        let policy = secure_execution::CommandEnv::new();
+        let mut cmd = std::process::Command::new("env");
+        cmd.secure_env(&policy);
+        assert_eq!(cmd.get_envs().count(), 0);
+        cmd.secure_env(&policy.always(true));
+        assert_eq!(cmd.get_envs().count(), 1);
+        let env = secure_execution::SecureEnv::new()
+            .allow("HOME", secure_execution::Validator::any())
+            .allow("HOME", secure_execution::Validator::max_len(0));
+        let policy = secure_execution::CommandEnv::from(env).always(true);
+        let mut cmd = std::process::Command::new("env");
+        cmd.secure_env(&policy);
+        assert_eq!(
+            cmd.get_envs().count(),
+            std::env::var_os("HOME").iter().count() + 1
+        );
+    }
+    #[cfg(unix)]
+    {
+        use secure_execution::{HardenCommandExt, Hardening};
+        let status = std::process::Command::new("/bin/sh")
+            .arg("-c")
+            .arg("exit 0")
+            .harden(&Hardening::new().always(true))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        assert_eq!(secure_execution::secure_path(), path);
+    }
+    #[cfg(unix)]
+    assert!(secure_execution::find_trusted_executable("../sh").is_none());
+    let locale = secure_execution::secure_locale(secure_execution::LocaleCategory::Messages);
+    assert_eq!(locale.nlspath, std::env::var_os("NLSPATH"));
+    assert!(locale.rejected.is_empty());
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    if let Some(home) = std::env::var_os("HOME").filter(|h| !h.is_empty()) {
+        assert_eq!(secure_execution::dirs::home_dir(), Some(home.into()));
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    {
+        let user = secure_execution::invoking_user().unwrap();
+        let id = |arg: &str| {
+            let output = std::process::Command::new("id").arg(arg).output().unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        assert_eq!(user.uid.to_string(), id("-ru"));
+        assert_eq!(user.name.to_str().unwrap(), id("-run"));
+        assert_eq!(user.group.gid.to_string(), id("-rg"));
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    {
+        assert_eq!(
+            secure_execution::secure_temp_dir().unwrap(),
+            std::env::temp_dir()
+        );
+        let (_, path) = secure_execution::secure_tempfile().unwrap();
+        assert!(path.starts_with(std::env::temp_dir()));
+        std::fs::remove_file(path).unwrap();
+    }
+    #[cfg(unix)]
+    if std::env::var_os("TZ").is_none() {
+        assert_eq!(
+            secure_execution::secure_timezone().zone,
+            secure_execution::TimeZone::File(secure_execution::DEFAULT_TZFILE.into())
+        );
+    }
+    #[cfg(unix)]
+    {
+        let policy = secure_execution::ScrubPolicy::new();
+        let count = std::env::vars_os().count();
+        let audit = secure_execution::EnvAudit::new()
+            .max_value_len(0)
+            .fatal_in_secure_execution(true);
+        // SAFETY: No other threads exist.
+        assert!(unsafe { audit.run() }.is_ok());
+        // SAFETY: No other threads exist.
+        let report = unsafe { secure_execution::scrub_environment(&policy) };
+        assert!(!report.scrubbed);
+        assert_eq!(std::env::vars_os().count(), count);
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        // SAFETY: No other threads exist.
+        unsafe { secure_execution::reexec_with_clean_environment(&policy) }.unwrap();
+    }
 }
@@ -0,0 +1,6 @@
+use secure_execution::{requires_secure_execution, secure_execution_reason, SecureExecutionReason};
+
+fn main() {
+    assert!(requires_secure_execution());
+    assert_eq!(secure_execution_reason(), SecureExecutionReason::Forced);
+}
@@ -0,0 +1,26 @@
+use secure_execution::{requires_secure_execution, testing};
+
+fn main() {
+    let real = requires_secure_execution();
+    {
+        let _guard = testing::override_global(!real);
+        assert_eq!(requires_secure_execution(), !real);
+        assert_eq!(secure_execution::refresh_secure_execution(), !real);
+        assert_eq!(
+            secure_execution::requires_secure_execution_uncached(),
+            !real
+        );
+        {
+            let _guard = testing::override_thread(real);
+            assert_eq!(requires_secure_execution(), real);
+        }
+        assert_eq!(requires_secure_execution(), !real);
+        std::thread::spawn(move || {
+            let _guard = testing::override_thread(true);
+            assert!(requires_secure_execution());
+        })
+        .join()
+        .unwrap();
+    }
+    assert_eq!(requires_secure_execution(), real);
+}
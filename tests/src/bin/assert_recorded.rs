@@ -0,0 +1,65 @@
+fn main() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use secure_execution::{
+            auxv::{self, Auxv, Entry, AT_NULL, AT_SECURE, AT_UID},
+            backend, requires_secure_execution, requires_secure_execution_uncached, Backend,
+        };
+
+        let arg = c"assert_recorded".as_ptr() as usize;
+        let env = c"A=1".as_ptr() as usize;
+        let stack: &[usize] = Box::leak(Box::new([
+            1,
+            arg,
+            0,
+            env,
+            0,
+            AT_SECURE as usize,
+            0,
+            AT_UID as usize,
+            1000,
+            AT_NULL as usize,
+            0,
+        ]));
+        // SAFETY: The fabricated stack has the layout set up by the kernel and is never
+        // modified or deallocated.
+        unsafe { auxv::init_from_initial_stack(stack.as_ptr()) };
+        assert!(!requires_secure_execution_uncached());
+        assert_eq!(Auxv::snapshot().secure(), Some(false));
+        assert_eq!(Auxv::snapshot().uid(), Some(1000));
+        assert_eq!(Auxv::snapshot().euid(), None);
+        assert_eq!(
+            auxv::entries().unwrap().collect::<Vec<_>>(),
+            [
+                Entry {
+                    key: AT_SECURE,
+                    value: 0
+                },
+                Entry {
+                    key: AT_UID,
+                    value: 1000
+                },
+            ]
+        );
+
+        let envp: &[usize] = Box::leak(Box::new([
+            env,
+            0,
+            AT_SECURE as usize,
+            1,
+            AT_UID as usize,
+            1000,
+            AT_NULL as usize,
+            0,
+        ]));
+        // SAFETY: The fabricated environment is followed by an auxiliary vector and is
+        // never modified or deallocated.
+        unsafe { auxv::init_from_environ(envp.as_ptr().cast()) };
+        assert!(requires_secure_execution());
+        assert_eq!(Auxv::snapshot().secure(), Some(true));
+        assert_eq!(auxv::getauxval(AT_SECURE), Some(1));
+        assert_eq!(auxv::getauxval(AT_UID), Some(1000));
+        assert_eq!(auxv::getauxval(AT_NULL), None);
+        assert_eq!(backend(), Backend::Getauxval);
+    }
+}
@@ -0,0 +1,19 @@
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[link(name = "c")]
+unsafe extern "C" {
+    safe fn setuid(uid: u32) -> i32;
+    safe fn setgid(gid: u32) -> i32;
+}
+
+// This test must be run as root. It drops its privileges like a daemon and checks that
+// the process is considered tainted afterwards.
+fn main() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use secure_execution::{requires_secure_execution_with, Semantics};
+
+        assert_eq!(setgid(65534), 0);
+        assert_eq!(setuid(65534), 0);
+        assert!(requires_secure_execution_with(Semantics::Tainted));
+    }
+}
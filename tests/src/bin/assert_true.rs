@@ -1,6 +1,208 @@
-use secure_execution::requires_secure_execution;
+use secure_execution::{requires_secure_execution, secure_execution_reason, SecureExecutionReason};
 
 fn main() {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let policy = secure_execution::ScrubPolicy::new()
+            .allow("HOME", secure_execution::Validator::any())
+            .allow("PATH", secure_execution::Validator::any())
+            .reset("TMPDIR", "/tmp");
+        let exe = Some(c"/proc/self/exe");
+        if secure_execution::auxv::Auxv::snapshot().execfn() != exe {
+            // Forces a re-exec. A guard in the environment must not prevent it.
+            std::env::set_var("SECURE_EXECUTION_REEXEC", std::process::id().to_string());
+        }
+        // SAFETY: No other threads exist.
+        unsafe { secure_execution::reexec_with_clean_environment(&policy) }.unwrap();
+        assert_eq!(secure_execution::auxv::Auxv::snapshot().execfn(), exe);
+        assert!(std::env::vars_os().all(|(n, _)| n == "HOME" || n == "PATH" || n == "TMPDIR"));
+        assert_eq!(std::env::var_os("TMPDIR").unwrap(), "/tmp");
+        std::env::set_var("FOO", "1");
+        // SAFETY: No other threads exist.
+        let res = unsafe { secure_execution::reexec_with_clean_environment(&policy) };
+        assert!(matches!(res, Err(secure_execution::ReexecError::Loop)));
+        std::env::remove_var("FOO");
+        let audit = secure_execution::EnvAudit::new()
+            .max_value_len(usize::MAX)
+            .fatal_in_secure_execution(true);
+        // SAFETY: No other threads exist.
+        assert_eq!(unsafe { audit.run() }, Ok(vec![]));
+    }
     assert!(requires_secure_execution());
     assert!(requires_secure_execution());
+    assert!(secure_execution::requires_secure_execution_uncached());
+    assert!(secure_execution::refresh_secure_execution());
+    assert!(requires_secure_execution());
+    assert_eq!(secure_execution_reason(), SecureExecutionReason::SetUid);
+    assert!(secure_execution::requires_secure_execution_with(
+        secure_execution::Semantics::Tainted
+    ));
+    assert_eq!(
+        secure_execution::secure_execution(),
+        secure_execution::SecureExecution::Required
+    );
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(
+        secure_execution::auxv::Auxv::snapshot().secure(),
+        Some(true)
+    );
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(
+        secure_execution::backend(),
+        secure_execution::Backend::Getauxval
+    );
+    assert_eq!(secure_execution::secure_var_os("PATH"), None);
+    assert!(secure_execution::secure_var("PATH").is_err());
+    assert_eq!(secure_execution::secure_vars_os().count(), 0);
+    let env = secure_execution::SecureEnv::new()
+        .allow("PATH", secure_execution::Validator::max_len(0))
+        .allow("HOME", secure_execution::Validator::any());
+    assert_eq!(env.var_os("PATH"), Err(secure_execution::Denied::Invalid));
+    assert_eq!(env.var_os("HOME"), Ok(std::env::var_os("HOME")));
+    assert_eq!(
+        env.var_os("TERM"),
+        Err(secure_execution::Denied::NotAllowed)
+    );
+    #[cfg(unix)]
+    {
+        let env = secure_execution::SecureEnv::new()
+            .allow("ROOT_FILE", secure_execution::Validator::root_owned_path());
+        let check = |value: &str| {
+            std::env::set_var("ROOT_FILE", value);
+            env.var_os("ROOT_FILE")
+        };
+        assert_eq!(check("/"), Ok(Some("/".into())));
+        assert_eq!(check("/tmp"), Err(secure_execution::Denied::Invalid));
+        assert_eq!(check("tmp"), Err(secure_execution::Denied::Invalid));
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        assert_eq!(
+            check("/proc/self/exe"),
+            Err(secure_execution::Denied::Invalid)
+        );
+        std::env::remove_var("ROOT_FILE");
+    }
+    #[cfg(unix)]
+    {
+        use secure_execution::SecureCommandExt;
+        let policy = secure_execution::CommandEnv::new()
+            .allow("HOME", secure_execution::Validator::any())
+            .allow("LD_PRELOAD", secure_execution::Validator::any());
+        let output = std::process::Command::new("/usr/bin/env")
+            .env("FOO", "1")
+            .secure_env(&policy)
+            .output()
+            .unwrap();
+        let mut expected = String::new();
+        if let Ok(home) = std::env::var("HOME") {
+            expected = format!("HOME={home}\n");
+        }
+        expected.push_str("PATH=/usr/sbin:/usr/bin:/sbin:/bin\n");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+    #[cfg(unix)]
+    {
+        use {
+            secure_execution::{HardenCommandExt, Hardening},
+            std::os::fd::AsRawFd,
+        };
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let fd = file.as_raw_fd();
+        let output = std::process::Command::new("/bin/sh")
+            .arg("-c")
+            .arg(format!("id -u; id -ru; [ -e /dev/fd/{fd} ] && echo kept"))
+            .harden(&Hardening::new().keep_fd(fd))
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<_> = stdout.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], lines[1]);
+        assert_eq!(lines[2], "kept");
+    }
+    assert_eq!(
+        secure_execution::secure_path(),
+        secure_execution::DEFAULT_TRUSTED_PATH
+    );
+    #[cfg(unix)]
+    assert!(secure_execution::find_trusted_executable("sh").is_some());
+    {
+        use secure_execution::{secure_locale, LocaleCategory, FALLBACK_LOCALE};
+        for name in ["LC_ALL", "LC_MESSAGES", "LANG", "LOCPATH", "NLSPATH"] {
+            std::env::remove_var(name);
+        }
+        std::env::set_var("LANG", "../../tmp/evil");
+        std::env::set_var("NLSPATH", "/tmp/%N");
+        let locale = secure_locale(LocaleCategory::Messages);
+        assert_eq!(locale.locale, FALLBACK_LOCALE);
+        assert_eq!(locale.nlspath, None);
+        assert_eq!(locale.rejected, ["LANG", "NLSPATH"]);
+        std::env::set_var("LANG", "en_US.UTF-8");
+        std::env::remove_var("NLSPATH");
+        let locale = secure_locale(LocaleCategory::Messages);
+        assert_eq!(locale.locale, "en_US.UTF-8");
+        assert!(locale.rejected.is_empty());
+        std::env::remove_var("LANG");
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    if let Some(home) = secure_execution::dirs::home_dir() {
+        use std::os::unix::fs::MetadataExt;
+        let uid = std::fs::metadata(&home).unwrap().uid();
+        assert_eq!(
+            std::process::Command::new("id")
+                .arg("-ru")
+                .output()
+                .unwrap()
+                .stdout,
+            format!("{uid}\n").into_bytes()
+        );
+        if let Some(config) = secure_execution::dirs::config_dir() {
+            assert_eq!(config, home.join(".config"));
+        }
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    {
+        let user = secure_execution::invoking_user().unwrap();
+        let id = |arg: &str| {
+            let output = std::process::Command::new("id").arg(arg).output().unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+        assert_eq!(user.uid.to_string(), id("-ru"));
+        assert_eq!(user.name.to_str().unwrap(), id("-run"));
+        assert_eq!(user.group.gid.to_string(), id("-rg"));
+    }
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            secure_execution::secure_temp_dir().unwrap(),
+            std::path::Path::new("/tmp")
+        );
+        let (file, path) = secure_execution::secure_tempfile().unwrap();
+        assert!(path.starts_with("/tmp"));
+        assert_eq!(file.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+        std::fs::remove_file(path).unwrap();
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let tz = secure_execution::secure_timezone();
+        assert!(tz.rejected.is_empty());
+        assert_eq!(
+            tz.zone,
+            secure_execution::TimeZone::File(secure_execution::DEFAULT_TZFILE.into())
+        );
+    }
+    #[cfg(unix)]
+    {
+        let policy = secure_execution::ScrubPolicy::new()
+            .allow("HOME", secure_execution::Validator::any())
+            .reset("PATH", "/usr/bin:/bin");
+        let home = std::env::var_os("HOME");
+        // SAFETY: No other threads exist.
+        let report = unsafe { secure_execution::scrub_environment(&policy) };
+        assert!(report.scrubbed);
+        assert_eq!(report.reset, ["PATH"]);
+        assert_eq!(std::env::var_os("HOME"), home);
+        assert_eq!(std::env::var_os("PATH").unwrap(), "/usr/bin:/bin");
+        assert_eq!(std::env::vars_os().count(), home.iter().count() + 1);
+    }
 }